@@ -0,0 +1,71 @@
+//! Compares the Arc-linked BhvNode tree with the linear BVH, the Grid and the k-d tree on the
+//! random spheres scene.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use raytracer::bvh::BhvNode;
+use raytracer::grid::Grid;
+use raytracer::hittable::Hittable;
+use raytracer::kd_tree::KdTree;
+use raytracer::linear_bvh::LinearBvh;
+use raytracer::ray::Ray;
+use raytracer::scenes::scene_random_spheres::create_world;
+use raytracer::utilities::random_float_range;
+use raytracer::vector::Point3;
+
+/// Rays from around the camera of the scene towards its spheres.
+fn camera_rays(total: usize) -> Vec<Ray> {
+    (0..total)
+        .map(|_| {
+            let origin = Point3::new(13.0, 2.0, 3.0);
+            let target = Point3::new(
+                random_float_range(-11.0, 11.0),
+                random_float_range(0.0, 2.0),
+                random_float_range(-11.0, 11.0),
+            );
+            Ray::new(origin, target - origin, 0.0)
+        })
+        .collect()
+}
+
+fn trace(bvh: &impl Hittable, rays: &[Ray]) -> usize {
+    rays.iter()
+        .filter(|ray| bvh.hit(ray, 0.001, f64::INFINITY).is_some())
+        .count()
+}
+
+fn bvh_benchmark(c: &mut Criterion) {
+    let world = create_world();
+    let rays = camera_rays(10_000);
+    let total = world.total_shapes();
+
+    let mut group = c.benchmark_group("random spheres");
+    group.bench_function("build tree", |b| {
+        b.iter(|| BhvNode::new(&mut world.clone(), 0, total, 0.0, 1.0))
+    });
+    group.bench_function("build linear", |b| {
+        b.iter(|| LinearBvh::new(black_box(&world), 0.0, 1.0))
+    });
+    group.bench_function("build grid", |b| {
+        b.iter(|| Grid::new(black_box(&world), 0.0, 1.0))
+    });
+    group.bench_function("build k-d tree", |b| {
+        b.iter(|| KdTree::new(black_box(&world), 0.0, 1.0))
+    });
+
+    let tree = BhvNode::new(&mut world.clone(), 0, total, 0.0, 1.0);
+    let linear = LinearBvh::new(&world, 0.0, 1.0);
+    let grid = Grid::new(&world, 0.0, 1.0);
+    let kd_tree = KdTree::new(&world, 0.0, 1.0);
+    group.bench_function("trace tree", |b| b.iter(|| trace(&tree, black_box(&rays))));
+    group.bench_function("trace linear", |b| {
+        b.iter(|| trace(&linear, black_box(&rays)))
+    });
+    group.bench_function("trace grid", |b| b.iter(|| trace(&grid, black_box(&rays))));
+    group.bench_function("trace k-d tree", |b| {
+        b.iter(|| trace(&kd_tree, black_box(&rays)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bvh_benchmark);
+criterion_main!(benches);
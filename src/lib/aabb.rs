@@ -5,6 +5,10 @@
 use crate::ray::Ray;
 use crate::vector::Point3;
 
+/// Padding applied to the thin dimensions of flat shapes so their bounding boxes are not
+/// degenerate.
+pub(crate) const BOX_PADDING: f64 = 0.0001;
+
 /// Type for AABBs
 #[derive(Debug, Clone, Copy, Default)]
 pub struct AaBb {
@@ -35,6 +39,24 @@ impl AaBb {
         true
     }
 
+    /// Returns the range of distances in [t_min, t_max] along the ray that are inside the AABB,
+    /// or None if the ray misses it.
+    pub fn hit_interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inverse = 1.0 / ray.direction()[a];
+            let t0 = (self.minimum[a] - ray.origin()[a]) * inverse;
+            let t1 = (self.maximum[a] - ray.origin()[a]) * inverse;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
     /// Creates and returns an owned AABB.
     pub fn new(a: Point3, b: Point3) -> Self {
         Self {
@@ -52,6 +74,17 @@ impl AaBb {
         self.maximum
     }
 
+    /// Returns the center of the AABB.
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    /// Returns the area of the surface of the AABB.
+    pub fn surface_area(&self) -> f64 {
+        let size = self.maximum - self.minimum;
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
     /// Computes the bounding box for two boxes.
     pub fn surrounding_box(box0: AaBb, box1: AaBb) -> Self {
         let small = Point3::new(
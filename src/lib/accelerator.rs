@@ -0,0 +1,160 @@
+//! This module defines the acceleration structures a World can be built with, and the traversal
+//! statistics they report: the linear Bounding Volume Hierarchy, the multi-level uniform Grid
+//! and the k-d tree.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::grid::Grid;
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::kd_tree::KdTree;
+use crate::linear_bvh::LinearBvh;
+use crate::ray::Ray;
+use serde::Deserialize;
+use std::ops::AddAssign;
+
+/// Counts of the work done to trace Rays through an acceleration structure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraversalStats {
+    /// Number of Rays traced.
+    pub rays: u64,
+    /// Number of nodes or cells visited.
+    pub node_visits: u64,
+    /// Number of Ray-shape intersection tests.
+    pub primitive_tests: u64,
+}
+
+impl TraversalStats {
+    /// Returns the average number of nodes or cells visited per Ray.
+    pub fn node_visits_per_ray(&self) -> f64 {
+        self.node_visits as f64 / self.rays.max(1) as f64
+    }
+
+    /// Returns the average number of intersection tests per Ray.
+    pub fn primitive_tests_per_ray(&self) -> f64 {
+        self.primitive_tests as f64 / self.rays.max(1) as f64
+    }
+}
+
+impl AddAssign for TraversalStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.rays += rhs.rays;
+        self.node_visits += rhs.node_visits;
+        self.primitive_tests += rhs.primitive_tests;
+    }
+}
+
+/// An acceleration structure that can count the work it does for each Ray.
+pub trait Accelerator: Hittable {
+    /// Finds the closest hit like 'hit', adding the nodes it visits and the shapes it tests to
+    /// the statistics.
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord>;
+}
+
+/// Kinds of acceleration structure, as named in the config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AcceleratorKind {
+    /// Linear Bounding Volume Hierarchy with SAH splits.
+    #[default]
+    Bvh,
+    /// Uniform grid whose crowded cells hold nested grids.
+    Grid,
+    /// k-d tree with SAH splits.
+    KdTree,
+}
+
+/// Enum dispatch of the acceleration structures.
+#[derive(Debug, Clone)]
+pub enum Accelerators {
+    /// Linear Bounding Volume Hierarchy.
+    Bvh(LinearBvh),
+    /// Multi-level uniform grid.
+    Grid(Grid),
+    /// k-d tree.
+    KdTree(KdTree),
+}
+
+impl Accelerators {
+    /// Builds an acceleration structure of the kind over the shapes, which must all have a
+    /// bounding box over the time interval.
+    pub fn new(kind: AcceleratorKind, list: &HittableList, time0: f64, time1: f64) -> Self {
+        match kind {
+            AcceleratorKind::Bvh => Accelerators::Bvh(LinearBvh::new(list, time0, time1)),
+            AcceleratorKind::Grid => Accelerators::Grid(Grid::new(list, time0, time1)),
+            AcceleratorKind::KdTree => Accelerators::KdTree(KdTree::new(list, time0, time1)),
+        }
+    }
+
+    /// Returns the kind of the acceleration structure.
+    pub fn kind(&self) -> AcceleratorKind {
+        match self {
+            Accelerators::Bvh(_) => AcceleratorKind::Bvh,
+            Accelerators::Grid(_) => AcceleratorKind::Grid,
+            Accelerators::KdTree(_) => AcceleratorKind::KdTree,
+        }
+    }
+
+    /// Updates the structure to a new time interval. The hierarchy is refitted, or rebuilt when
+    /// its cost grew past the threshold; grids and k-d trees are always rebuilt. Returns whether
+    /// the structure was rebuilt.
+    pub fn update(&mut self, time0: f64, time1: f64, threshold: f64) -> bool {
+        match self {
+            Accelerators::Bvh(bvh) => bvh.refit_or_rebuild(time0, time1, threshold),
+            Accelerators::Grid(grid) => {
+                let list = HittableList {
+                    objects: grid.shapes().to_vec(),
+                };
+                *grid = Grid::new(&list, time0, time1);
+                true
+            }
+            Accelerators::KdTree(tree) => {
+                let list = HittableList {
+                    objects: tree.shapes().to_vec(),
+                };
+                *tree = KdTree::new(&list, time0, time1);
+                true
+            }
+        }
+    }
+}
+
+impl Accelerator for Accelerators {
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord> {
+        match self {
+            Accelerators::Bvh(bvh) => bvh.hit_counted(ray, t_min, t_max, stats),
+            Accelerators::Grid(grid) => grid.hit_counted(ray, t_min, t_max, stats),
+            Accelerators::KdTree(tree) => tree.hit_counted(ray, t_min, t_max, stats),
+        }
+    }
+}
+
+impl Hittable for Accelerators {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        match self {
+            Accelerators::Bvh(bvh) => bvh.hit(ray, t_min, t_max),
+            Accelerators::Grid(grid) => grid.hit(ray, t_min, t_max),
+            Accelerators::KdTree(tree) => tree.hit(ray, t_min, t_max),
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        match self {
+            Accelerators::Bvh(bvh) => bvh.bounding_box(time0, time1),
+            Accelerators::Grid(grid) => grid.bounding_box(time0, time1),
+            Accelerators::KdTree(tree) => tree.bounding_box(time0, time1),
+        }
+    }
+}
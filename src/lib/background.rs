@@ -0,0 +1,86 @@
+//! This module defines the Background type that provides the color of Rays that do not hit any
+//! shape in a Scene.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::color::Color;
+use crate::ray::Ray;
+use serde::Deserialize;
+
+/// Background (environment) of a Scene.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Background {
+    /// A single color in every direction.
+    Solid {
+        /// Color of the background.
+        color: Color,
+    },
+    /// A vertical gradient that blends from the bottom color to the top color.
+    Gradient {
+        /// Color when looking straight up.
+        top: Color,
+        /// Color when looking straight down.
+        bottom: Color,
+    },
+    /// No environment: Rays that miss every shape gather no light.
+    None,
+}
+
+impl Background {
+    /// Returns the classic blue-white sky gradient.
+    pub fn sky() -> Self {
+        Background::Gradient {
+            top: Color::new(0.5, 0.7, 1.0),
+            bottom: Color::white(),
+        }
+    }
+
+    /// Returns the color of the background in the direction of the Ray.
+    pub fn value(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Solid { color } => *color,
+            Background::Gradient { top, bottom } => {
+                let unit_direction = ray.direction().to_unit();
+                let t = 0.5 * (unit_direction.y + 1.0);
+                (1.0 - t) * *bottom + t * *top
+            }
+            Background::None => Color::black(),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::sky()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Background;
+    use crate::color::Color;
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn background_values_by_direction() {
+        let ray = |y: f64| Ray::new(Point3::zeroes(), Vec3::new(1.0, y, 0.0), 0.0);
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+
+        let solid = Background::Solid { color: red };
+        assert_eq!(solid.value(&ray(1e9)), red);
+        assert_eq!(solid.value(&ray(-1e9)), red);
+
+        let gradient = Background::Gradient {
+            top: blue,
+            bottom: red,
+        };
+        assert_eq!(gradient.value(&ray(1e9)), blue);
+        assert_eq!(gradient.value(&ray(-1e9)), red);
+        assert_eq!(gradient.value(&ray(0.0)), Color::new(0.5, 0.0, 0.5));
+
+        assert_eq!(Background::None.value(&ray(1.0)), Color::black());
+    }
+}
@@ -1,4 +1,5 @@
-//! This module provides the functionality to render Scenes and write an Image file as output.
+//! This module defines the Bounding Volume Hierarchy (BVH), a binary tree of bounding boxes
+//! built with the surface area heuristic that accelerates Ray intersections with many shapes.
 
 #![warn(missing_docs, missing_debug_implementations)]
 #![allow(unused_assignments, clippy::write_with_newline)]
@@ -7,10 +8,102 @@ use crate::aabb::AaBb;
 use crate::hittable::{HitRecord, Hittable, HittableList};
 use crate::ray::Ray;
 use crate::shapes::HittableObjects;
-use crate::utilities::random_usize_range;
+use crate::vector::Point3;
 use std::cmp::Ordering;
 use std::sync::Arc;
 
+/// Number of bins of centroids along an axis whose boundaries are candidate splits.
+const SAH_BINS: usize = 16;
+
+/// A shape with its bounding box, while a hierarchy is built.
+pub(crate) struct BuildItem {
+    /// The shape.
+    pub(crate) object: HittableObjects,
+    /// Bounding box of the shape, or None if it is unbounded.
+    pub(crate) bbox: Option<AaBb>,
+}
+
+/// Returns the centroid of the box of a bounded item.
+fn centroid_of(item: &BuildItem) -> Point3 {
+    item.bbox.map_or(Point3::zeroes(), |bbox| bbox.centroid())
+}
+
+/// Returns the box that surrounds an optional box and another one.
+fn surround(bbox: Option<AaBb>, other: AaBb) -> AaBb {
+    bbox.map_or(other, |bbox| AaBb::surrounding_box(bbox, other))
+}
+
+/// Sorts bounded items along the best axis and returns the number of items that go to the
+/// left child. Candidate splits are the boundaries between bins of centroids, and the cost
+/// of each is the surface area of the box of each side times its number of items.
+pub(crate) fn sah_split(items: &mut [BuildItem]) -> usize {
+    let boxes: Vec<AaBb> = items.iter().filter_map(|item| item.bbox).collect();
+    let centroids: Vec<Point3> = boxes.iter().map(AaBb::centroid).collect();
+    let (mut low, mut high) = (centroids[0], centroids[0]);
+    for c in &centroids[1..] {
+        low = Point3::new(low.x.min(c.x), low.y.min(c.y), low.z.min(c.z));
+        high = Point3::new(high.x.max(c.x), high.y.max(c.y), high.z.max(c.z));
+    }
+
+    let mut best: Option<(f64, usize, usize)> = None;
+    for axis in 0..3 {
+        let extent = high[axis] - low[axis];
+        if extent <= 0.0 {
+            continue;
+        }
+        let bin_of = |c: &Point3| {
+            (((c[axis] - low[axis]) / extent * SAH_BINS as f64) as usize).min(SAH_BINS - 1)
+        };
+        let mut counts = [0usize; SAH_BINS];
+        let mut bin_boxes: [Option<AaBb>; SAH_BINS] = [None; SAH_BINS];
+        for (bbox, c) in boxes.iter().zip(&centroids) {
+            let bin = bin_of(c);
+            counts[bin] += 1;
+            bin_boxes[bin] = Some(surround(bin_boxes[bin], *bbox));
+        }
+
+        // Areas and counts of the bins to the right of each boundary, swept from the right.
+        let mut right_area = [0.0; SAH_BINS];
+        let mut right_count = [0usize; SAH_BINS];
+        let (mut bbox, mut count) = (None, 0);
+        for bin in (1..SAH_BINS).rev() {
+            if let Some(bin_box) = bin_boxes[bin] {
+                bbox = Some(surround(bbox, bin_box));
+            }
+            count += counts[bin];
+            right_area[bin] = bbox.map_or(0.0, |b: AaBb| b.surface_area());
+            right_count[bin] = count;
+        }
+
+        let (mut bbox, mut count) = (None, 0);
+        for bin in 0..SAH_BINS - 1 {
+            if let Some(bin_box) = bin_boxes[bin] {
+                bbox = Some(surround(bbox, bin_box));
+            }
+            count += counts[bin];
+            let right = right_count[bin + 1];
+            if count == 0 || right == 0 {
+                continue;
+            }
+            let left_area = bbox.map_or(0.0, |b: AaBb| b.surface_area());
+            let cost = left_area * count as f64 + right_area[bin + 1] * right as f64;
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, axis, count));
+            }
+        }
+    }
+
+    match best {
+        Some((_, axis, count)) => {
+            // Sorting by centroid keeps the items of the lower bins first.
+            items.sort_by(|a, b| centroid_of(a)[axis].total_cmp(&centroid_of(b)[axis]));
+            count
+        }
+        // All centroids coincide, so no split is better than another.
+        None => items.len() / 2,
+    }
+}
+
 /// Bounding Volume Hierarchy Node type
 #[derive(Debug, Clone)]
 pub struct BhvNode {
@@ -18,56 +111,62 @@ pub struct BhvNode {
     left: Arc<HittableObjects>,
     /// Right node.
     right: Arc<HittableObjects>,
-    /// AABB box.
-    box_aabb: AaBb,
+    /// AABB box, or None when one of the children is unbounded.
+    box_aabb: Option<AaBb>,
 }
 
 impl BhvNode {
-    /// Creates a new BhvNode and adds it to the tree
+    /// Creates a hierarchy over the shapes in list[start..end], splitting them where the surface
+    /// area heuristic estimates the cheapest traversal.
     pub fn new(list: &mut HittableList, start: usize, end: usize, time0: f64, time1: f64) -> Self {
-        let axis = random_usize_range(0, 2);
+        let mut items: Vec<BuildItem> = list.objects[start..end]
+            .iter()
+            .map(|object| BuildItem {
+                bbox: object.bounding_box(time0, time1),
+                object: object.clone(),
+            })
+            .collect();
+        let node = Self::build(&mut items);
 
-        let comparator = match axis {
-            0 => BhvNode::box_x_compare,
-            1 => BhvNode::box_y_compare,
-            2 => BhvNode::box_z_compare,
-            _ => panic!("random usize range out of bounds"),
-        };
-
-        let left;
-        let right;
-        let object_span = end - start;
-
-        if object_span == 1 {
-            left = list.objects[start].clone();
-            right = list.objects[start].clone();
-        } else if object_span == 2 {
-            if BhvNode::box_compare_axis(&list.objects[start], &list.objects[start + 1], axis)
-                == Ordering::Less
-            {
-                left = list.objects[start].clone();
-                right = list.objects[start + 1].clone()
-            } else {
-                left = list.objects[start + 1].clone();
-                right = list.objects[start].clone();
-            }
-        } else {
-            // list.objects.sort();
-            list.objects.sort_by(comparator);
-            let mid = start + object_span / 2;
+        // Leave the shapes in the order of the leaves of the hierarchy.
+        for (slot, item) in list.objects[start..end].iter_mut().zip(items) {
+            *slot = item.object;
+        }
+        node
+    }
 
-            left = HittableObjects::BhvNode(Self::new(list, start, mid, time0, time1));
-            right = HittableObjects::BhvNode(Self::new(list, mid, end, time0, time1));
+    /// Builds the node over the items, reordering them so each child covers a contiguous range.
+    fn build(items: &mut [BuildItem]) -> Self {
+        if items.len() == 1 {
+            let object = Arc::new(items[0].object.clone());
+            return Self {
+                left: Arc::clone(&object),
+                right: object,
+                box_aabb: items[0].bbox,
+            };
         }
 
-        let box_left = left
-            .bounding_box(time0, time1)
-            .expect("No bounding box in bhv_node constructor");
-        let box_right = left
-            .bounding_box(time0, time1)
-            .expect("No bounding box in bhv_node constructor");
+        // Unbounded shapes, such as planes, are kept together in their own subtree.
+        items.sort_by_key(|item| item.bbox.is_none());
+        let bounded = items.iter().take_while(|item| item.bbox.is_some()).count();
+        let mid = if bounded == 0 {
+            items.len() / 2
+        } else if bounded < items.len() {
+            bounded
+        } else {
+            sah_split(items)
+        };
 
-        let box_aabb = AaBb::surrounding_box(box_left, box_right);
+        let (left_items, right_items) = items.split_at_mut(mid);
+        let left = Self::child(left_items);
+        let right = Self::child(right_items);
+        // The item boxes cover the whole time interval, so moving shapes stay inside. Unbounded
+        // items make the node unbounded so it is always visited.
+        let box_aabb = items
+            .iter()
+            .map(|item| item.bbox)
+            .reduce(|a, b| Some(AaBb::surrounding_box(a?, b?)))
+            .flatten();
 
         Self {
             left: Arc::new(left),
@@ -76,15 +175,29 @@ impl BhvNode {
         }
     }
 
-    #[rustfmt::skip]
-    /// Compare AaBb boxes via their axis
+    /// Returns the shape of a single item, or a node over several.
+    fn child(items: &mut [BuildItem]) -> HittableObjects {
+        if items.len() == 1 {
+            items[0].object.clone()
+        } else {
+            HittableObjects::BhvNode(Self::build(items))
+        }
+    }
+
+    /// Compare AaBb boxes via their axis. Shapes without a box are sorted last.
     pub fn box_compare_axis(a: &HittableObjects, b: &HittableObjects, axis: usize) -> Ordering {
-        let box_a = a.bounding_box(0.0, 0.0).expect("No bounding box in bhd_node constructor");
-        let box_b = b.bounding_box(0.0, 0.0).expect("No bounding box in bhd_node constructor");
+        let min_a = a
+            .bounding_box(0.0, 0.0)
+            .map_or(f64::INFINITY, |box_a| box_a.min()[axis]);
+        let min_b = b
+            .bounding_box(0.0, 0.0)
+            .map_or(f64::INFINITY, |box_b| box_b.min()[axis]);
 
-        if box_a.min()[axis] < box_b.min()[axis] {
+        if min_a < min_b {
             Ordering::Less
-        } else{ Ordering::Greater}
+        } else {
+            Ordering::Greater
+        }
     }
 
     /// Compare AaBb boxes via their X axis
@@ -100,14 +213,12 @@ impl BhvNode {
         BhvNode::box_compare_axis(a, b, 2)
     }
 
-    /// Full Eq comparison of AaBb boxes.
+    /// Full Eq comparison of AaBb boxes. Shapes without a box are only equal to each other.
     pub fn box_equal(a: HittableObjects, b: HittableObjects) -> bool {
-        let box_a = a
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
-        let box_b = b
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
+        let (box_a, box_b) = match (a.bounding_box(0.0, 0.0), b.bounding_box(0.0, 0.0)) {
+            (Some(box_a), Some(box_b)) => (box_a, box_b),
+            (box_a, box_b) => return box_a.is_none() && box_b.is_none(),
+        };
         box_a.min()[0] == box_b.min()[0]
             && box_a.min()[1] == box_b.min()[1]
             && box_a.min()[2] == box_b.min()[2]
@@ -119,22 +230,20 @@ impl BhvNode {
 
 impl Hittable for BhvNode {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        if !self.box_aabb.hit(ray, t_min, t_max) {
-            return None;
+        if let Some(box_aabb) = &self.box_aabb {
+            if !box_aabb.hit(ray, t_min, t_max) {
+                return None;
+            }
         }
         let hit_left = self.left.hit(ray, t_min, t_max);
-        let hit_right: Option<HitRecord>;
+        let closest_so_far = hit_left.as_ref().map_or(t_max, |hit| hit.t);
 
-        if let Some(hit) = hit_left {
-            hit_right = self.right.hit(ray, t_min, hit.t);
-        } else {
-            hit_right = self.right.hit(ray, t_min, t_max);
-        }
-        hit_right
+        // Keep the left hit unless the right child is hit closer.
+        self.right.hit(ray, t_min, closest_so_far).or(hit_left)
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
-        Some(self.box_aabb)
+        self.box_aabb
     }
 }
 
@@ -142,35 +251,13 @@ impl Eq for HittableObjects {}
 
 impl Ord for HittableObjects {
     fn cmp(&self, other: &Self) -> Ordering {
-        let box_a = self
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
-        let box_b = other
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
-
-        if box_a.min()[0] < box_b.min()[0] {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
+        BhvNode::box_compare_axis(self, other, 0)
     }
 }
 
 impl PartialOrd<Self> for HittableObjects {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let box_a = self
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
-        let box_b = other
-            .bounding_box(0.0, 0.0)
-            .expect("No bounding box in bhd_node constructor");
-
-        if box_a.min()[0] < box_b.min()[0] {
-            Some(Ordering::Less)
-        } else {
-            Some(Ordering::Greater)
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -179,3 +266,107 @@ impl PartialEq<Self> for HittableObjects {
         BhvNode::box_equal(self.clone(), other.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BhvNode;
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::ray::Ray;
+    use crate::shapes::cuboid::Cuboid;
+    use crate::shapes::moving_sphere::MovingSphere;
+    use crate::shapes::plane::Plane;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::utilities::random_float_range;
+    use crate::vector::{Point3, Vec3};
+
+    fn random_point(range: f64) -> Point3 {
+        Point3::new(
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+        )
+    }
+
+    fn random_scene(total: usize) -> HittableList {
+        let material = HitRecord::default().material;
+        let mut list = HittableList::default();
+        for i in 0..total {
+            let center = random_point(10.0);
+            let size = random_float_range(0.05, 1.5);
+            list.add(if i % 4 == 0 {
+                let half = Vec3::new(size, size / 2.0, size);
+                HittableObjects::Cuboid(Cuboid::new(center - half, center + half, material.clone()))
+            } else {
+                HittableObjects::Sphere(Sphere::new(center, size, material.clone()))
+            });
+        }
+        list
+    }
+
+    fn assert_same_hits(bvh: &BhvNode, list: &HittableList) {
+        for _ in 0..2000 {
+            let origin = random_point(15.0);
+            let time = random_float_range(0.0, 1.0);
+            let ray = Ray::new(origin, random_point(10.0) - origin, time);
+            let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            let found = bvh.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn bvh_matches_brute_force_on_random_scenes() {
+        for total in [1, 2, 3, 17, 300] {
+            let list = random_scene(total);
+            let mut shapes = list.clone();
+            let bvh = BhvNode::new(&mut shapes, 0, total, 0.0, 1.0);
+            assert_eq!(shapes.total_shapes(), total);
+            assert_same_hits(&bvh, &list);
+        }
+    }
+
+    #[test]
+    fn bvh_keeps_unbounded_and_coincident_shapes() {
+        let material = HitRecord::default().material;
+        let mut list = random_scene(40);
+        for _ in 0..5 {
+            list.add(HittableObjects::Sphere(Sphere::new(
+                Point3::new(1.0, 2.0, 3.0),
+                0.5,
+                material.clone(),
+            )));
+        }
+        list.add(HittableObjects::Plane(Plane::new(
+            Point3::new(0.0, -8.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            material,
+        )));
+        let total = list.total_shapes();
+        let bvh = BhvNode::new(&mut list.clone(), 0, total, 0.0, 1.0);
+
+        assert!(bvh.bounding_box(0.0, 1.0).is_none());
+        assert_same_hits(&bvh, &list);
+    }
+
+    #[test]
+    fn bvh_bounds_moving_shapes_over_the_time_interval() {
+        let material = HitRecord::default().material;
+        let mut list = random_scene(30);
+        for _ in 0..30 {
+            let center = random_point(10.0);
+            list.add(HittableObjects::MovingSphere(MovingSphere::new(
+                center,
+                center + random_point(5.0),
+                0.0,
+                1.0,
+                random_float_range(0.2, 1.0),
+                material.clone(),
+            )));
+        }
+        let total = list.total_shapes();
+        let bvh = BhvNode::new(&mut list.clone(), 0, total, 0.0, 1.0);
+
+        assert_same_hits(&bvh, &list);
+    }
+}
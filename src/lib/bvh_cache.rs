@@ -0,0 +1,598 @@
+//! The BVH cache module stores built Triangle Meshes, their vertex buffers, faces and linear
+//! hierarchy, in a versioned binary file, so that repeated renders of the same model skip the
+//! construction of the hierarchy.
+//!
+//! A cache file starts with a header that holds the magic bytes, the version of the format and
+//! the key of the geometry, a hash of the vertex buffers and faces of the Mesh. The mesh section
+//! and the hierarchy section follow, with all numbers stored in little-endian order. Materials
+//! are not stored; they are supplied again when the cache is loaded.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::color::Color;
+use crate::linear_bvh::{LinearBvh, LinearNode, STACK_SIZE};
+use crate::loaders::data_error;
+use crate::materials::Materials;
+use crate::shapes::triangle_mesh::{Mesh, MeshFace, MeshTriangle, TriangleMesh};
+use crate::shapes::HittableObjects;
+use crate::vector::Point3;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Bytes at the start of every cache file.
+const MAGIC: &[u8; 8] = b"RTBVHCCH";
+/// Version of the format. Files written with another version are ignored and rebuilt.
+pub const CACHE_VERSION: u32 = 1;
+/// Size of the header: magic, version and key.
+const HEADER_SIZE: usize = 8 + 4 + 8;
+/// Extension of the cache files created by 'load_or_build'.
+const CACHE_EXTENSION: &str = "bvh";
+
+/// Returns the key of the geometry of a Mesh: a hash of its vertex buffers and faces. Any change
+/// to the geometry changes the key and so invalidates the cache files written for it.
+pub fn geometry_key(mesh: &Mesh) -> u64 {
+    let mut writer = Writer::default();
+    writer.mesh(mesh);
+    fnv1a_64(&writer.data)
+}
+
+/// Writes a Triangle Mesh and its hierarchy to a cache file for the key.
+pub fn save_mesh_cache(path: &Path, key: u64, mesh: &TriangleMesh) -> Result<(), Error> {
+    let mut writer = Writer::default();
+    writer.data.extend_from_slice(MAGIC);
+    writer.u32(CACHE_VERSION);
+    writer.u64(key);
+    writer.mesh(mesh.mesh());
+
+    let bvh = mesh.bvh();
+    writer.usize(bvh.nodes().len());
+    for node in bvh.nodes() {
+        writer.point(node.bbox.min());
+        writer.point(node.bbox.max());
+        writer.usize(node.offset);
+        writer.usize(node.count);
+        writer.u8(node.axis as u8);
+        writer.u8(node.descending as u8);
+    }
+    writer.usize(bvh.shapes().len());
+    for shape in bvh.shapes() {
+        match shape {
+            HittableObjects::MeshTriangle(triangle) => writer.usize(triangle.face()),
+            _ => unreachable!("the hierarchy of a triangle mesh only holds its triangles"),
+        }
+    }
+
+    // Write to a temporary file first, so that an interrupted write never leaves a truncated
+    // cache behind.
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, &writer.data)?;
+    fs::rename(&temporary, path)
+}
+
+/// Reads a Triangle Mesh and its hierarchy from a cache file, with the materials its faces refer
+/// to. Returns None when the file does not exist or was written for another version or key, and
+/// an InvalidData error when it is damaged.
+pub fn load_mesh_cache(
+    path: &Path,
+    key: u64,
+    materials: Vec<Materials>,
+) -> Result<Option<TriangleMesh>, Error> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let file = path.display().to_string();
+    if data.len() < HEADER_SIZE || &data[..8] != MAGIC {
+        return Err(data_error(&file, "not a BVH cache file"));
+    }
+    let mut reader = Reader {
+        data: &data,
+        offset: MAGIC.len(),
+        file: &file,
+    };
+    if reader.u32()? != CACHE_VERSION || reader.u64()? != key {
+        return Ok(None);
+    }
+
+    let mut mesh = reader.mesh()?;
+    if mesh.materials_count != materials.len() {
+        return Err(data_error(
+            &file,
+            &format!(
+                "the faces use {} materials, but {} were given",
+                mesh.materials_count,
+                materials.len()
+            ),
+        ));
+    }
+    mesh.mesh.materials = materials;
+    let mesh = Arc::new(mesh.mesh);
+
+    let count = reader.length(6 * 8 + 2 * 8 + 2)?;
+    let mut nodes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let minimum = reader.point()?;
+        let maximum = reader.point()?;
+        nodes.push(LinearNode {
+            bbox: AaBb::new(minimum, maximum),
+            offset: reader.usize()?,
+            count: reader.usize()?,
+            axis: reader.u8()? as usize,
+            descending: reader.u8()? != 0,
+        });
+    }
+    let count = reader.length(8)?;
+    let mut shapes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let face = reader.index(mesh.faces.len(), "face")?;
+        shapes.push(HittableObjects::MeshTriangle(MeshTriangle::new(
+            Arc::clone(&mesh),
+            face,
+        )));
+    }
+    if reader.offset != data.len() {
+        return Err(data_error(&file, "unexpected data after the hierarchy"));
+    }
+    validate_nodes(&nodes, shapes.len(), &file)?;
+
+    Ok(Some(TriangleMesh::from_parts(
+        mesh,
+        LinearBvh::from_parts(nodes, shapes),
+    )))
+}
+
+/// Returns the Triangle Mesh of the Mesh, read from the cache directory when it holds a file for
+/// the geometry, or built and written to the directory otherwise. Files that are damaged or
+/// were written by another version are rebuilt.
+pub fn load_or_build(mesh: Mesh, cache_dir: &Path) -> Result<TriangleMesh, Error> {
+    let key = geometry_key(&mesh);
+    let path = cache_path(cache_dir, key);
+    match load_mesh_cache(&path, key, mesh.materials.clone()) {
+        Ok(Some(cached)) => return Ok(cached),
+        Ok(None) => {}
+        Err(err) if err.kind() == ErrorKind::InvalidData => {}
+        Err(err) => return Err(err),
+    }
+
+    let built = TriangleMesh::new(mesh);
+    fs::create_dir_all(cache_dir)?;
+    save_mesh_cache(&path, key, &built)?;
+    Ok(built)
+}
+
+/// Returns the path of the cache file for a key in the cache directory.
+pub fn cache_path(cache_dir: &Path, key: u64) -> PathBuf {
+    cache_dir.join(format!("{:016x}.{}", key, CACHE_EXTENSION))
+}
+
+/// Returns the 64-bit FNV-1a hash of the data.
+fn fnv1a_64(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Checks that the nodes form a tree below the first node whose leaves refer to valid ranges of
+/// shapes, and which is shallow enough for the traversal stack.
+fn validate_nodes(nodes: &[LinearNode], total_shapes: usize, file: &str) -> Result<(), Error> {
+    if nodes.is_empty() || total_shapes == 0 {
+        return Err(data_error(file, "empty hierarchy"));
+    }
+    let mut depths: Vec<Option<usize>> = vec![None; nodes.len()];
+    depths[0] = Some(0);
+    for (index, node) in nodes.iter().enumerate() {
+        let depth =
+            depths[index].ok_or_else(|| data_error(file, &format!("orphan node {}", index)))?;
+        if node.count > 0 {
+            if node.offset.saturating_add(node.count) > total_shapes {
+                return Err(data_error(
+                    file,
+                    &format!("leaf {} refers to shapes out of range", index),
+                ));
+            }
+            continue;
+        }
+        if node.axis > 2 || depth + 1 >= STACK_SIZE {
+            return Err(data_error(
+                file,
+                &format!("invalid interior node {}", index),
+            ));
+        }
+        for child in [index + 1, node.offset] {
+            if child <= index || child >= nodes.len() || depths[child].is_some() {
+                return Err(data_error(
+                    file,
+                    &format!("node {} has an invalid child {}", index, child),
+                ));
+            }
+            depths[child] = Some(depth + 1);
+        }
+    }
+    Ok(())
+}
+
+/// Buffer that values are appended to in little-endian order.
+#[derive(Debug, Default)]
+struct Writer {
+    /// The bytes written so far.
+    data: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn point(&mut self, p: Point3) {
+        self.f64(p.x);
+        self.f64(p.y);
+        self.f64(p.z);
+    }
+
+    fn indices(&mut self, indices: Option<[usize; 3]>) {
+        match indices {
+            Some(indices) => {
+                self.u8(1);
+                indices.iter().for_each(|&i| self.usize(i));
+            }
+            None => self.u8(0),
+        }
+    }
+
+    /// Writes the vertex buffers and faces of the Mesh, and the number of its materials.
+    fn mesh(&mut self, mesh: &Mesh) {
+        self.usize(mesh.positions.len());
+        mesh.positions.iter().for_each(|&p| self.point(p));
+        self.usize(mesh.normals.len());
+        mesh.normals.iter().for_each(|&n| self.point(n));
+        self.usize(mesh.uvs.len());
+        for &(u, v) in &mesh.uvs {
+            self.f64(u);
+            self.f64(v);
+        }
+        self.usize(mesh.colors.len());
+        for color in &mesh.colors {
+            self.f64(color.r);
+            self.f64(color.g);
+            self.f64(color.b);
+        }
+        self.usize(mesh.faces.len());
+        for face in &mesh.faces {
+            face.positions.iter().for_each(|&i| self.usize(i));
+            self.indices(face.normals);
+            self.indices(face.uvs);
+            self.usize(face.material);
+        }
+        self.usize(mesh.materials.len());
+    }
+}
+
+/// A Mesh read from a cache, without its materials.
+struct CachedMesh {
+    /// Vertex buffers and faces.
+    mesh: Mesh,
+    /// Number of materials the faces refer to.
+    materials_count: usize,
+}
+
+/// Cursor over the data of a cache file that reports truncated or invalid data as errors.
+struct Reader<'a> {
+    /// Contents of the file.
+    data: &'a [u8],
+    /// Position of the next value.
+    offset: usize,
+    /// Name of the file, used to report errors.
+    file: &'a str,
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| data_error(self.file, "truncated BVH cache"))?;
+        self.offset += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.bytes()?))
+    }
+
+    fn usize(&mut self) -> Result<usize, Error> {
+        usize::try_from(self.u64()?).map_err(|_| data_error(self.file, "value out of range"))
+    }
+
+    fn f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.bytes()?))
+    }
+
+    fn point(&mut self) -> Result<Point3, Error> {
+        Ok(Point3::new(self.f64()?, self.f64()?, self.f64()?))
+    }
+
+    /// Reads the length of an array whose elements take at least 'element_size' bytes, and
+    /// checks that the rest of the file can hold it.
+    fn length(&mut self, element_size: usize) -> Result<usize, Error> {
+        let length = self.usize()?;
+        let remaining = self.data.len() - self.offset;
+        if length > remaining / element_size {
+            return Err(data_error(self.file, "truncated BVH cache"));
+        }
+        Ok(length)
+    }
+
+    /// Reads an index into an array of the given length.
+    fn index(&mut self, length: usize, name: &str) -> Result<usize, Error> {
+        let index = self.usize()?;
+        if index >= length {
+            return Err(data_error(
+                self.file,
+                &format!("{} index {} out of range", name, index),
+            ));
+        }
+        Ok(index)
+    }
+
+    fn indices(&mut self, length: usize, name: &str) -> Result<[usize; 3], Error> {
+        Ok([
+            self.index(length, name)?,
+            self.index(length, name)?,
+            self.index(length, name)?,
+        ])
+    }
+
+    fn optional_indices(&mut self, length: usize, name: &str) -> Result<Option<[usize; 3]>, Error> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.indices(length, name)?)),
+        }
+    }
+
+    /// Reads the vertex buffers and faces of a Mesh and checks all their indices.
+    fn mesh(&mut self) -> Result<CachedMesh, Error> {
+        let mut mesh = Mesh::default();
+        for _ in 0..self.length(3 * 8)? {
+            mesh.positions.push(self.point()?);
+        }
+        for _ in 0..self.length(3 * 8)? {
+            mesh.normals.push(self.point()?);
+        }
+        for _ in 0..self.length(2 * 8)? {
+            mesh.uvs.push((self.f64()?, self.f64()?));
+        }
+        for _ in 0..self.length(3 * 8)? {
+            mesh.colors
+                .push(Color::new(self.f64()?, self.f64()?, self.f64()?));
+        }
+        if !mesh.colors.is_empty() && mesh.colors.len() != mesh.positions.len() {
+            return Err(data_error(self.file, "one color per position expected"));
+        }
+        let faces = self.length(3 * 8 + 2 + 8)?;
+        let mut materials = Vec::with_capacity(faces);
+        for _ in 0..faces {
+            let positions = self.indices(mesh.positions.len(), "position")?;
+            let normals = self.optional_indices(mesh.normals.len(), "normal")?;
+            let uvs = self.optional_indices(mesh.uvs.len(), "texture coordinate")?;
+            materials.push(self.usize()?);
+            mesh.faces.push(MeshFace {
+                positions,
+                normals,
+                uvs,
+                material: *materials.last().unwrap(),
+            });
+        }
+        let materials_count = self.usize()?;
+        if materials
+            .iter()
+            .any(|&material| material >= materials_count)
+        {
+            return Err(data_error(self.file, "material index out of range"));
+        }
+        Ok(CachedMesh {
+            mesh,
+            materials_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cache_path, geometry_key, load_mesh_cache, load_or_build, save_mesh_cache, HEADER_SIZE,
+    };
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::loaders::obj::load_obj_cached;
+    use crate::loaders::ply::load_ply_cached;
+    use crate::loaders::stl::load_stl_cached;
+    use crate::ray::Ray;
+    use crate::shapes::triangle_mesh::{Mesh, MeshFace, TriangleMesh};
+    use crate::vector::{Point3, Vec3};
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    /// A wavy grid of 'n' x 'n' quads, each split into two triangles.
+    fn wavy_grid(n: usize, amplitude: f64) -> Mesh {
+        let mut mesh = Mesh::default();
+        for j in 0..=n {
+            for i in 0..=n {
+                let (x, z) = (i as f64, j as f64);
+                mesh.positions.push(Point3::new(
+                    x,
+                    amplitude * (x * 0.7).sin() * (z * 0.4).cos(),
+                    z,
+                ));
+            }
+        }
+        for j in 0..n {
+            for i in 0..n {
+                let corner = j * (n + 1) + i;
+                for positions in [
+                    [corner, corner + 1, corner + n + 2],
+                    [corner, corner + n + 2, corner + n + 1],
+                ] {
+                    mesh.faces.push(MeshFace {
+                        positions,
+                        normals: None,
+                        uvs: None,
+                        material: 0,
+                    });
+                }
+            }
+        }
+        mesh.materials.push(HitRecord::default().material);
+        mesh
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bvh_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn cached_mesh_gives_the_same_hits() {
+        let mesh = wavy_grid(20, 1.5);
+        let key = geometry_key(&mesh);
+        let materials = mesh.materials.clone();
+        let built = TriangleMesh::new(mesh);
+        let dir = cache_dir("round_trip");
+        fs::create_dir_all(&dir).unwrap();
+        let path = cache_path(&dir, key);
+        save_mesh_cache(&path, key, &built).unwrap();
+
+        let cached = load_mesh_cache(&path, key, materials).unwrap().unwrap();
+        assert_eq!(cached.total_triangles(), built.total_triangles());
+        assert_eq!(cached.bvh().total_nodes(), built.bvh().total_nodes());
+        for i in 0..400 {
+            let target = Point3::new((i % 20) as f64 + 0.3, 0.0, (i / 20) as f64 + 0.6);
+            let origin = Point3::new(10.0, 8.0, -5.0);
+            let ray = Ray::new(origin, target - origin, 0.0);
+            let expected = built.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            let actual = cached.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            assert_eq!(expected, actual);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn changed_geometry_or_version_invalidates_the_cache() {
+        let dir = cache_dir("invalidation");
+        let mesh = wavy_grid(4, 1.0);
+        let key = geometry_key(&mesh);
+        load_or_build(mesh.clone(), &dir).unwrap();
+        let path = cache_path(&dir, key);
+        assert!(path.exists());
+
+        // Moving a single vertex changes the key, so the old file is not used.
+        let mut moved = mesh.clone();
+        moved.positions[3].y += 1e-9;
+        let moved_key = geometry_key(&moved);
+        assert_ne!(moved_key, key);
+        assert!(load_mesh_cache(&path, moved_key, moved.materials.clone())
+            .unwrap()
+            .is_none());
+        let rebuilt = load_or_build(moved, &dir).unwrap();
+        assert!(cache_path(&dir, moved_key).exists());
+        let ray = Ray::new(Point3::new(0.5, 5.0, 0.2), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(rebuilt.hit(&ray, 0.001, f64::INFINITY).is_some());
+
+        // Files of another version are ignored.
+        let mut data = fs::read(&path).unwrap();
+        data[8] = data[8].wrapping_add(1);
+        fs::write(&path, &data).unwrap();
+        assert!(load_mesh_cache(&path, key, mesh.materials.clone())
+            .unwrap()
+            .is_none());
+
+        // Truncated files are errors, and are rebuilt by 'load_or_build'.
+        data[8] = data[8].wrapping_sub(1);
+        fs::write(&path, &data[..data.len() - 5]).unwrap();
+        let err = load_mesh_cache(&path, key, mesh.materials.clone())
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        fs::write(&path, &data[..HEADER_SIZE + 3]).unwrap();
+        assert!(load_mesh_cache(&path, key, mesh.materials.clone()).is_err());
+        load_or_build(mesh.clone(), &dir).unwrap();
+        assert!(load_mesh_cache(&path, key, mesh.materials)
+            .unwrap()
+            .is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn mesh_loaders_write_and_reuse_the_cache() {
+        let dir = cache_dir("loaders");
+        fs::create_dir_all(&dir).unwrap();
+        let files = [
+            ("tri.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"),
+            (
+                "tri.ply",
+                "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\n\
+                 property float y\nproperty float z\nelement face 1\n\
+                 property list uchar int vertex_indices\nend_header\n\
+                 0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n",
+            ),
+            (
+                "tri.stl",
+                "solid tri\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n\
+                 vertex 0 1 0\nendloop\nendfacet\nendsolid tri\n",
+            ),
+        ];
+        let material = HitRecord::default().material;
+        let load = |name: &str| {
+            let path = dir.join(name);
+            let cache = dir.join("cache");
+            match name {
+                "tri.obj" => load_obj_cached(&path, material.clone(), &cache),
+                "tri.ply" => load_ply_cached(&path, material.clone(), &cache),
+                _ => load_stl_cached(&path, material.clone(), &cache),
+            }
+            .unwrap()
+        };
+
+        for (name, data) in files {
+            fs::write(dir.join(name), data).unwrap();
+            let built = load(name);
+            let key = geometry_key(built.mesh());
+            assert!(cache_path(&dir.join("cache"), key).exists());
+            let cached = load(name);
+            assert_eq!(cached.total_triangles(), 1);
+            let ray = Ray::new(Point3::new(0.2, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            assert!(cached.hit(&ray, 0.001, f64::INFINITY).is_some());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
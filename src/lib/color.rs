@@ -7,10 +7,12 @@ use crate::utilities::EPSILON;
 use crate::vector::Vec3;
 use derive_more::{Add, Neg, Sub};
 use rand::prelude::*;
+use serde::Deserialize;
 use std::ops::{Div, Mul};
 
 /// The Color type in RGB form.
-#[derive(Debug, Copy, Clone, Add, Sub, Neg)]
+#[derive(Debug, Copy, Clone, Add, Sub, Neg, Deserialize)]
+#[serde(from = "[f64; 3]")]
 pub struct Color {
     /// Red component.
     pub r: f64,
@@ -104,6 +106,16 @@ impl From<Vec3> for Color {
     }
 }
 
+impl From<[f64; 3]> for Color {
+    fn from(value: [f64; 3]) -> Self {
+        Self {
+            r: value[0],
+            g: value[1],
+            b: value[2],
+        }
+    }
+}
+
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
         if (self.r - other.r).abs() > EPSILON
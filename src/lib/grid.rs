@@ -0,0 +1,392 @@
+//! This module defines the multi-level uniform Grid, which splits the box of the shapes into
+//! cells of equal size and walks the cells along a Ray with a 3D-DDA. Cells that hold many
+//! shapes hold a nested Grid over them instead.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::accelerator::{Accelerator, TraversalStats};
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::vector::Point3;
+
+/// Number of cells per shape a level aims for.
+const DENSITY: f64 = 3.0;
+/// Largest number of cells of a level along an axis.
+const MAX_RESOLUTION: usize = 64;
+/// Largest number of shapes in a cell before it holds a nested Grid.
+const MAX_CELL_SHAPES: usize = 8;
+/// Largest number of levels, including the top one.
+const MAX_LEVELS: usize = 3;
+/// Smallest extent of a level along an axis, so that flat scenes have cells of non-zero size.
+const MIN_EXTENT: f64 = 1e-4;
+
+/// A cell of a level of the Grid.
+#[derive(Debug, Clone)]
+enum GridCell {
+    /// Indices of the shapes whose boxes overlap the cell.
+    Shapes(Vec<usize>),
+    /// A finer Grid over the shapes of a crowded cell.
+    Nested(Box<GridLevel>),
+}
+
+/// A level of the Grid: a box split into cells of equal size.
+#[derive(Debug, Clone)]
+struct GridLevel {
+    /// Box covered by the cells.
+    bounds: AaBb,
+    /// Number of cells along each axis.
+    resolution: [usize; 3],
+    /// Size of the cells along each axis.
+    cell_size: [f64; 3],
+    /// Cells, ordered by X, then Y, then Z.
+    cells: Vec<GridCell>,
+}
+
+impl GridLevel {
+    /// Builds a level over the shapes with the given indices, inside the bounds.
+    fn build(boxes: &[AaBb], items: &[usize], bounds: AaBb, level: usize) -> Self {
+        // Widen flat bounds so that every axis has cells of non-zero size.
+        let mut minimum = bounds.min();
+        let mut maximum = bounds.max();
+        for axis in 0..3 {
+            let missing = MIN_EXTENT - (maximum[axis] - minimum[axis]);
+            if missing > 0.0 {
+                minimum[axis] -= missing / 2.0;
+                maximum[axis] += missing / 2.0;
+            }
+        }
+        let bounds = AaBb::new(minimum, maximum);
+
+        let extent = maximum - minimum;
+        let max_extent = extent.x.max(extent.y).max(extent.z);
+        let cells_per_unit = (DENSITY * items.len() as f64).cbrt() / max_extent;
+        let mut resolution = [1; 3];
+        let mut cell_size = [0.0; 3];
+        for axis in 0..3 {
+            resolution[axis] =
+                ((extent[axis] * cells_per_unit).round() as usize).clamp(1, MAX_RESOLUTION);
+            cell_size[axis] = extent[axis] / resolution[axis] as f64;
+        }
+        let mut grid = Self {
+            bounds,
+            resolution,
+            cell_size,
+            cells: Vec::new(),
+        };
+
+        let mut cells = vec![Vec::new(); resolution.iter().product()];
+        for &item in items {
+            let low = grid.cell_of(boxes[item].min());
+            let high = grid.cell_of(boxes[item].max());
+            for z in low[2]..=high[2] {
+                for y in low[1]..=high[1] {
+                    for x in low[0]..=high[0] {
+                        cells[grid.offset([x, y, z])].push(item);
+                    }
+                }
+            }
+        }
+
+        grid.cells = cells
+            .into_iter()
+            .enumerate()
+            .map(|(offset, shapes)| {
+                if shapes.len() <= MAX_CELL_SHAPES || level + 1 >= MAX_LEVELS {
+                    return GridCell::Shapes(shapes);
+                }
+                // The nested Grid covers the part of the cell that its shapes overlap.
+                let cell = grid.cell_bounds(offset);
+                let overlap = shapes
+                    .iter()
+                    .map(|&item| boxes[item])
+                    .reduce(AaBb::surrounding_box)
+                    .unwrap();
+                let minimum = Point3::new(
+                    cell.min().x.max(overlap.min().x),
+                    cell.min().y.max(overlap.min().y),
+                    cell.min().z.max(overlap.min().z),
+                );
+                let maximum = Point3::new(
+                    cell.max().x.min(overlap.max().x),
+                    cell.max().y.min(overlap.max().y),
+                    cell.max().z.min(overlap.max().z),
+                );
+                GridCell::Nested(Box::new(Self::build(
+                    boxes,
+                    &shapes,
+                    AaBb::new(minimum, maximum),
+                    level + 1,
+                )))
+            })
+            .collect();
+        grid
+    }
+
+    /// Returns the cell along an axis that contains a coordinate, clamped to the level.
+    fn cell_along(&self, axis: usize, coordinate: f64) -> usize {
+        let cell = ((coordinate - self.bounds.min()[axis]) / self.cell_size[axis]).floor();
+        (cell.max(0.0) as usize).min(self.resolution[axis] - 1)
+    }
+
+    /// Returns the cell that contains a point, clamped to the level.
+    fn cell_of(&self, p: Point3) -> [usize; 3] {
+        [
+            self.cell_along(0, p.x),
+            self.cell_along(1, p.y),
+            self.cell_along(2, p.z),
+        ]
+    }
+
+    /// Returns the position of a cell in the array of cells.
+    fn offset(&self, cell: [usize; 3]) -> usize {
+        (cell[2] * self.resolution[1] + cell[1]) * self.resolution[0] + cell[0]
+    }
+
+    /// Returns the box of the cell at a position in the array of cells.
+    fn cell_bounds(&self, offset: usize) -> AaBb {
+        let cell = [
+            offset % self.resolution[0],
+            offset / self.resolution[0] % self.resolution[1],
+            offset / (self.resolution[0] * self.resolution[1]),
+        ];
+        let corner = |shift: usize| {
+            let coordinate = |axis: usize| {
+                self.bounds.min()[axis] + (cell[axis] + shift) as f64 * self.cell_size[axis]
+            };
+            Point3::new(coordinate(0), coordinate(1), coordinate(2))
+        };
+        AaBb::new(corner(0), corner(1))
+    }
+
+    /// Walks the cells that the Ray crosses, nearest first, and returns the closest hit.
+    fn hit(
+        &self,
+        shapes: &[HittableObjects],
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.hit_interval(ray, t_min, t_max)?;
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        // The cell where the Ray enters the level, and for each axis the distance to the next
+        // cell boundary and between boundaries.
+        let mut cell = self.cell_of(ray.at(t_enter));
+        let mut t_next = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            let boundary = if direction[axis] > 0.0 {
+                cell[axis] + 1
+            } else {
+                cell[axis]
+            };
+            if direction[axis] != 0.0 {
+                let plane = self.bounds.min()[axis] + boundary as f64 * self.cell_size[axis];
+                t_next[axis] = (plane - origin[axis]) / direction[axis];
+                t_delta[axis] = self.cell_size[axis] / direction[axis].abs();
+            }
+        }
+
+        let mut closest_so_far = t_max;
+        let mut hit_anything = None;
+        loop {
+            stats.node_visits += 1;
+            match &self.cells[self.offset(cell)] {
+                GridCell::Shapes(items) => {
+                    stats.primitive_tests += items.len() as u64;
+                    for &item in items {
+                        if let Some(hit) = shapes[item].hit(ray, t_min, closest_so_far) {
+                            closest_so_far = hit.t;
+                            hit_anything = Some(hit);
+                        }
+                    }
+                }
+                GridCell::Nested(level) => {
+                    if let Some(hit) = level.hit(shapes, ray, t_min, closest_so_far, stats) {
+                        closest_so_far = hit.t;
+                        hit_anything = Some(hit);
+                    }
+                }
+            }
+
+            // Shapes may span several cells, so a hit only ends the walk once it lies before
+            // the exit from the current cell.
+            let axis = if t_next[0] < t_next[1] {
+                if t_next[0] < t_next[2] {
+                    0
+                } else {
+                    2
+                }
+            } else if t_next[1] < t_next[2] {
+                1
+            } else {
+                2
+            };
+            if closest_so_far <= t_next[axis] || t_next[axis] > t_exit {
+                break;
+            }
+            if direction[axis] > 0.0 {
+                cell[axis] += 1;
+                if cell[axis] == self.resolution[axis] {
+                    break;
+                }
+            } else {
+                if cell[axis] == 0 {
+                    break;
+                }
+                cell[axis] -= 1;
+            }
+            t_next[axis] += t_delta[axis];
+        }
+        hit_anything
+    }
+
+    /// Returns the number of cells of the level and its nested levels.
+    fn total_cells(&self) -> usize {
+        self.cells.len()
+            + self
+                .cells
+                .iter()
+                .map(|cell| match cell {
+                    GridCell::Shapes(_) => 0,
+                    GridCell::Nested(level) => level.total_cells(),
+                })
+                .sum::<usize>()
+    }
+}
+
+/// Multi-level uniform Grid over shapes that all have a bounding box.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    /// The shapes, referred to by index from the cells.
+    shapes: Vec<HittableObjects>,
+    /// Top level of the Grid, or None when there are no shapes.
+    root: Option<GridLevel>,
+}
+
+impl Grid {
+    /// Creates and returns an owned Grid over the shapes, with bounding boxes for the time
+    /// interval. Panics if a shape has no bounding box.
+    pub fn new(list: &HittableList, time0: f64, time1: f64) -> Self {
+        let boxes: Vec<AaBb> = list
+            .objects
+            .iter()
+            .map(|object| {
+                object
+                    .bounding_box(time0, time1)
+                    .expect("shapes in a grid need a bounding box")
+            })
+            .collect();
+        let root = boxes
+            .iter()
+            .copied()
+            .reduce(AaBb::surrounding_box)
+            .map(|bounds| {
+                let items: Vec<usize> = (0..boxes.len()).collect();
+                GridLevel::build(&boxes, &items, bounds, 0)
+            });
+        Self {
+            shapes: list.objects.clone(),
+            root,
+        }
+    }
+
+    /// Returns the shapes of the Grid.
+    pub fn shapes(&self) -> &[HittableObjects] {
+        &self.shapes
+    }
+
+    /// Returns the number of cells of all levels.
+    pub fn total_cells(&self) -> usize {
+        self.root.as_ref().map_or(0, GridLevel::total_cells)
+    }
+}
+
+impl Accelerator for Grid {
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord> {
+        self.root
+            .as_ref()?
+            .hit(&self.shapes, ray, t_min, t_max, stats)
+    }
+}
+
+impl Hittable for Grid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.hit_counted(ray, t_min, t_max, &mut TraversalStats::default())
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        self.root.as_ref().map(|root| root.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::ray::Ray;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::utilities::random_float_range;
+    use crate::vector::{Point3, Vec3};
+
+    fn random_point(range: f64) -> Point3 {
+        Point3::new(
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+        )
+    }
+
+    #[test]
+    fn grid_matches_brute_force() {
+        let material = HitRecord::default().material;
+        for total in [0, 1, 5, 500] {
+            // Spheres spread over the scene, and a dense cluster that gets nested levels.
+            let mut list = HittableList::default();
+            for i in 0..total {
+                let (center, radius) = if i % 2 == 0 {
+                    (random_point(10.0), random_float_range(0.05, 1.0))
+                } else {
+                    (random_point(0.5), random_float_range(0.01, 0.1))
+                };
+                list.add(HittableObjects::Sphere(Sphere::new(
+                    center,
+                    radius,
+                    material.clone(),
+                )));
+            }
+            let grid = Grid::new(&list, 0.0, 1.0);
+            assert_eq!(grid.shapes().len(), total);
+            assert_eq!(grid.bounding_box(0.0, 1.0).is_some(), total > 0);
+            if total == 500 {
+                let top_level_cells = grid.root.as_ref().unwrap().cells.len();
+                assert!(grid.total_cells() > top_level_cells);
+            }
+
+            for i in 0..2000 {
+                let origin = random_point(15.0);
+                // Some rays run along the axes, where the walk never steps along two of them.
+                let direction = match i % 4 {
+                    0 => Vec3::new(-origin.x, 0.0, 0.0),
+                    1 => Vec3::new(0.0, -origin.y, 0.0),
+                    _ => random_point(2.0) - origin,
+                };
+                let ray = Ray::new(origin, direction, 0.0);
+                let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                let found = grid.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                assert_eq!(found, expected);
+            }
+        }
+    }
+}
@@ -30,6 +30,9 @@ pub struct HitRecord {
     pub v: f64,
     /// Used to determine if the Ray was inside (false) or outside (true) a shape when it hit.
     pub front_face: bool,
+    /// Color that multiplies the attenuation of the material, such as an interpolated vertex
+    /// color. White leaves the material unchanged.
+    pub tint: Color,
 }
 
 impl HitRecord {
@@ -60,6 +63,7 @@ impl HitRecord {
             u,
             v,
             front_face,
+            tint: Color::white(),
         }
     }
 }
@@ -74,6 +78,7 @@ impl Default for HitRecord {
             u: 0.0,
             v: 0.0,
             front_face: false,
+            tint: Color::white(),
         }
     }
 }
@@ -86,10 +91,44 @@ pub trait Hittable {
     /// The bounding box function of a shape that implements the Hittable trait.
     /// Returns None if shape has no primitive boxes (e.g. infinite plane)
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb>;
+
+    /// Returns every intersection of a Ray with the shape between t_min and t_max, nearest
+    /// first. Constructive solid geometry uses it to find where Rays enter and leave shapes.
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        hits_along(self, ray, t_min, t_max)
+    }
+}
+
+/// Maximum number of intersections collected along a single Ray.
+const MAX_HITS: usize = 64;
+/// Distance moved past each intersection before looking for the next one.
+const HIT_ALL_EPSILON: f64 = 1e-7;
+
+/// Collects the intersections of a Ray with a shape by repeatedly asking for the closest hit
+/// beyond the previous one.
+pub(crate) fn hits_along<H: Hittable + ?Sized>(
+    shape: &H,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Vec<HitRecord> {
+    let mut hits: Vec<HitRecord> = Vec::new();
+    let mut t = t_min;
+    while hits.len() < MAX_HITS {
+        match shape.hit(ray, t, t_max) {
+            Some(hit) => {
+                t = hit.t + HIT_ALL_EPSILON * hit.t.abs().max(1.0);
+                hits.push(hit);
+            }
+            None => break,
+        }
+    }
+    hits
 }
 
 /// Structure that allows the collection of Shapes to create a Scene.
 /// An instance is commonly defined as "World".
+#[derive(Debug, Clone)]
 pub struct HittableList {
     /// Collection of shapes that define a Scene to be rendered.
     pub objects: Vec<HittableObjects>,
@@ -125,7 +164,7 @@ impl Hittable for HittableList {
         let mut closest_so_far = t_max;
 
         for shape in self.objects.iter() {
-            if let Some(hit) = shape.hit(&ray, t_min, closest_so_far) {
+            if let Some(hit) = shape.hit(ray, t_min, closest_so_far) {
                 closest_so_far = hit.t;
                 hit_something = Some(hit)
             }
@@ -138,12 +177,12 @@ impl Hittable for HittableList {
             return None;
         }
 
-        let temp_box = AaBb::default();
         let mut output_box = AaBb::default();
         let mut first_box = true;
 
         for shape in self.objects.iter() {
-            shape.bounding_box(time0, time1)?;
+            // A single unbounded shape makes the whole collection unbounded.
+            let temp_box = shape.bounding_box(time0, time1)?;
             if first_box {
                 output_box = temp_box;
             } else {
@@ -0,0 +1,390 @@
+//! This module defines the k-d tree, which splits space recursively with axis-aligned planes
+//! placed by the Surface Area Heuristic, and traverses the nodes along a Ray front to back.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::accelerator::{Accelerator, TraversalStats};
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+
+/// Largest number of shapes stored in a leaf without looking for a split.
+const MAX_LEAF_SHAPES: usize = 2;
+/// Estimated cost of visiting an interior node, relative to testing a shape.
+const TRAVERSAL_COST: f64 = 1.0;
+/// Estimated cost of testing a Ray against a shape.
+const INTERSECTION_COST: f64 = 80.0;
+/// Fraction of the cost saved by splits that leave one side empty.
+const EMPTY_BONUS: f64 = 0.5;
+/// Number of splits along a path that may cost more than the leaf they replace.
+const MAX_BAD_REFINES: usize = 3;
+
+/// A node of the k-d tree. The child below the plane of an interior node is stored right after
+/// it, so only the index of the child above is kept.
+#[derive(Debug, Clone, Copy)]
+enum KdNode {
+    /// A node split by a plane.
+    Interior {
+        /// Axis the plane is perpendicular to.
+        axis: usize,
+        /// Position of the plane along the axis.
+        split: f64,
+        /// Index of the child above the plane.
+        above: usize,
+    },
+    /// A node whose shapes are tested.
+    Leaf {
+        /// Position of the first shape index of the leaf.
+        first: usize,
+        /// Number of shapes of the leaf.
+        count: usize,
+    },
+}
+
+/// An end of the extent of a shape along an axis, used to sweep candidate planes.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    /// Position of the end along the axis.
+    t: f64,
+    /// Whether the extent starts at the end, rather than stops.
+    starts: bool,
+    /// Index of the shape.
+    item: usize,
+}
+
+/// k-d tree over shapes that all have a bounding box.
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    /// The shapes, referred to by index from the leaves.
+    shapes: Vec<HittableObjects>,
+    /// Indices of the shapes of the leaves; a shape that straddles a plane is in several.
+    indices: Vec<usize>,
+    /// Nodes in depth first order.
+    nodes: Vec<KdNode>,
+    /// Box of all the shapes.
+    bounds: Option<AaBb>,
+}
+
+impl KdTree {
+    /// Creates and returns an owned k-d tree over the shapes, with bounding boxes for the time
+    /// interval. Panics if a shape has no bounding box.
+    pub fn new(list: &HittableList, time0: f64, time1: f64) -> Self {
+        let boxes: Vec<AaBb> = list
+            .objects
+            .iter()
+            .map(|object| {
+                object
+                    .bounding_box(time0, time1)
+                    .expect("shapes in a k-d tree need a bounding box")
+            })
+            .collect();
+        let bounds = boxes.iter().copied().reduce(AaBb::surrounding_box);
+        let mut tree = Self {
+            shapes: list.objects.clone(),
+            indices: Vec::new(),
+            nodes: Vec::new(),
+            bounds,
+        };
+        if let Some(bounds) = bounds {
+            let max_depth = (8.0 + 1.3 * (boxes.len() as f64).log2()).round() as usize;
+            let items: Vec<usize> = (0..boxes.len()).collect();
+            tree.build(&boxes, items, bounds, max_depth, 0);
+        }
+        tree
+    }
+
+    /// Returns the shapes of the tree.
+    pub fn shapes(&self) -> &[HittableObjects] {
+        &self.shapes
+    }
+
+    /// Returns the number of nodes of the tree.
+    pub fn total_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Appends the node over the items inside the bounds and its descendants.
+    fn build(
+        &mut self,
+        boxes: &[AaBb],
+        items: Vec<usize>,
+        bounds: AaBb,
+        depth_left: usize,
+        bad_refines: usize,
+    ) {
+        if items.len() <= MAX_LEAF_SHAPES || depth_left == 0 {
+            self.push_leaf(items);
+            return;
+        }
+
+        // Sweep the ends of the shapes along the longest axis, then along the others if no
+        // plane inside the bounds was found.
+        let extent = bounds.max() - bounds.min();
+        let total_area = bounds.surface_area();
+        let leaf_cost = INTERSECTION_COST * items.len() as f64;
+        let mut best: Option<(f64, usize, usize)> = None;
+        let mut best_edges = Vec::new();
+        let mut axis = if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        };
+        for _ in 0..3 {
+            if total_area <= 0.0 {
+                break;
+            }
+            let mut edges: Vec<Edge> = items
+                .iter()
+                .flat_map(|&item| {
+                    [
+                        Edge {
+                            t: boxes[item].min()[axis],
+                            starts: true,
+                            item,
+                        },
+                        Edge {
+                            t: boxes[item].max()[axis],
+                            starts: false,
+                            item,
+                        },
+                    ]
+                })
+                .collect();
+            // At equal positions, starts come before stops, so flat shapes are never lost.
+            edges.sort_by(|a, b| a.t.total_cmp(&b.t).then(b.starts.cmp(&a.starts)));
+
+            let (other0, other1) = ((axis + 1) % 3, (axis + 2) % 3);
+            let cap_area = extent[other0] * extent[other1];
+            let side_length = extent[other0] + extent[other1];
+            let (mut below, mut above) = (0, items.len());
+            for (i, edge) in edges.iter().enumerate() {
+                if !edge.starts {
+                    above -= 1;
+                }
+                if edge.t > bounds.min()[axis] && edge.t < bounds.max()[axis] {
+                    let below_area = 2.0 * (cap_area + (edge.t - bounds.min()[axis]) * side_length);
+                    let above_area = 2.0 * (cap_area + (bounds.max()[axis] - edge.t) * side_length);
+                    let bonus = if below == 0 || above == 0 {
+                        EMPTY_BONUS
+                    } else {
+                        0.0
+                    };
+                    let cost = TRAVERSAL_COST
+                        + INTERSECTION_COST
+                            * (1.0 - bonus)
+                            * (below_area * below as f64 + above_area * above as f64)
+                            / total_area;
+                    if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                        best = Some((cost, axis, i));
+                    }
+                }
+                if edge.starts {
+                    below += 1;
+                }
+            }
+            if best.is_some() {
+                best_edges = edges;
+                break;
+            }
+            axis = (axis + 1) % 3;
+        }
+
+        let Some((cost, axis, offset)) = best else {
+            self.push_leaf(items);
+            return;
+        };
+        let bad_refines = bad_refines + usize::from(cost > leaf_cost);
+        if (cost > 4.0 * leaf_cost && items.len() < 16) || bad_refines == MAX_BAD_REFINES {
+            self.push_leaf(items);
+            return;
+        }
+
+        // Shapes that start before the plane go below it, and shapes that stop after it above.
+        let split = best_edges[offset].t;
+        let below: Vec<usize> = best_edges[..offset]
+            .iter()
+            .filter(|edge| edge.starts)
+            .map(|edge| edge.item)
+            .collect();
+        let above: Vec<usize> = best_edges[offset + 1..]
+            .iter()
+            .filter(|edge| !edge.starts)
+            .map(|edge| edge.item)
+            .collect();
+        let mut below_bounds = bounds;
+        below_bounds.maximum[axis] = split;
+        let mut above_bounds = bounds;
+        above_bounds.minimum[axis] = split;
+
+        let index = self.nodes.len();
+        self.nodes.push(KdNode::Interior {
+            axis,
+            split,
+            above: 0,
+        });
+        self.build(boxes, below, below_bounds, depth_left - 1, bad_refines);
+        let above_index = self.nodes.len();
+        if let KdNode::Interior { above, .. } = &mut self.nodes[index] {
+            *above = above_index;
+        }
+        self.build(boxes, above, above_bounds, depth_left - 1, bad_refines);
+    }
+
+    /// Appends a leaf with the items.
+    fn push_leaf(&mut self, items: Vec<usize>) {
+        self.nodes.push(KdNode::Leaf {
+            first: self.indices.len(),
+            count: items.len(),
+        });
+        self.indices.extend(items);
+    }
+}
+
+impl Accelerator for KdTree {
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord> {
+        let (mut node_min, mut node_max) = self.bounds?.hit_interval(ray, t_min, t_max)?;
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        let mut closest_so_far = t_max;
+        let mut hit_anything = None;
+        // Nodes still to visit, with the range of distances of the Ray inside them.
+        let mut stack: Vec<(usize, f64, f64)> = Vec::new();
+        let mut current = 0;
+        loop {
+            // Nodes beyond a hit cannot hold a closer one.
+            if closest_so_far < node_min {
+                break;
+            }
+            stats.node_visits += 1;
+            match self.nodes[current] {
+                KdNode::Interior { axis, split, above } => {
+                    let t_plane = (split - origin[axis]) / direction[axis];
+                    let below_first =
+                        origin[axis] < split || (origin[axis] == split && direction[axis] <= 0.0);
+                    let (first, second) = if below_first {
+                        (current + 1, above)
+                    } else {
+                        (above, current + 1)
+                    };
+                    if t_plane > node_max || t_plane <= 0.0 {
+                        current = first;
+                    } else if t_plane < node_min {
+                        current = second;
+                    } else {
+                        stack.push((second, t_plane, node_max));
+                        current = first;
+                        node_max = t_plane;
+                    }
+                    continue;
+                }
+                KdNode::Leaf { first, count } => {
+                    stats.primitive_tests += count as u64;
+                    for &item in &self.indices[first..first + count] {
+                        if let Some(hit) = self.shapes[item].hit(ray, t_min, closest_so_far) {
+                            closest_so_far = hit.t;
+                            hit_anything = Some(hit);
+                        }
+                    }
+                }
+            }
+            match stack.pop() {
+                Some((next, next_min, next_max)) => {
+                    current = next;
+                    node_min = next_min;
+                    node_max = next_max;
+                }
+                None => break,
+            }
+        }
+        hit_anything
+    }
+}
+
+impl Hittable for KdTree {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.hit_counted(ray, t_min, t_max, &mut TraversalStats::default())
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KdTree;
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::ray::Ray;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::xz_rect::XzRect;
+    use crate::shapes::HittableObjects;
+    use crate::utilities::random_float_range;
+    use crate::vector::{Point3, Vec3};
+
+    fn random_point(range: f64) -> Point3 {
+        Point3::new(
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+        )
+    }
+
+    #[test]
+    fn kd_tree_matches_brute_force() {
+        let material = HitRecord::default().material;
+        for total in [0, 1, 5, 500] {
+            let mut list = HittableList::default();
+            for i in 0..total {
+                // Flat rectangles share the planes of their boxes with many candidate splits.
+                if i % 5 == 0 {
+                    let (x, z) = (
+                        random_float_range(-10.0, 8.0),
+                        random_float_range(-10.0, 8.0),
+                    );
+                    list.add(HittableObjects::XzRect(XzRect::new(
+                        x,
+                        x + 2.0,
+                        z,
+                        z + 2.0,
+                        0.0,
+                        material.clone(),
+                    )));
+                } else {
+                    list.add(HittableObjects::Sphere(Sphere::new(
+                        random_point(10.0),
+                        random_float_range(0.05, 1.0),
+                        material.clone(),
+                    )));
+                }
+            }
+            let tree = KdTree::new(&list, 0.0, 1.0);
+            assert_eq!(tree.shapes().len(), total);
+            assert_eq!(tree.bounding_box(0.0, 1.0).is_some(), total > 0);
+            if total == 500 {
+                assert!(tree.total_nodes() > 100);
+            }
+
+            for i in 0..2000 {
+                let origin = random_point(15.0);
+                let direction = match i % 4 {
+                    0 => Vec3::new(0.0, -origin.y, 0.0),
+                    _ => random_point(10.0) - origin,
+                };
+                let ray = Ray::new(origin, direction, 0.0);
+                let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                let found = tree.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                assert_eq!(found, expected);
+            }
+        }
+    }
+}
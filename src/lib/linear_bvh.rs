@@ -0,0 +1,414 @@
+//! This module defines the linear Bounding Volume Hierarchy, which stores its nodes in a
+//! contiguous array and refers to its shapes by index, and traverses them without recursion.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::accelerator::{Accelerator, TraversalStats};
+use crate::bvh::{sah_split, BuildItem};
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::vector::{Point3, Vec3};
+
+/// Largest number of shapes stored in a leaf.
+const MAX_LEAF_SHAPES: usize = 4;
+/// Depth from which nodes are split in halves, which bounds the depth of the tree.
+const MEDIAN_SPLIT_DEPTH: usize = 32;
+/// Capacity of the traversal stack, which holds at most one node per level of the tree.
+pub(crate) const STACK_SIZE: usize = 64;
+/// Estimated cost of testing a Ray against the box of a node, relative to testing a shape.
+const TRAVERSAL_COST: f64 = 1.0;
+/// Estimated cost of testing a Ray against a shape.
+const INTERSECTION_COST: f64 = 1.0;
+
+/// A node of the linear hierarchy. The first child of an interior node is stored right after
+/// it, so only the index of the second child is kept.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LinearNode {
+    /// Box that contains the shapes below the node.
+    pub(crate) bbox: AaBb,
+    /// Index of the first shape of a leaf, or of the second child of an interior node.
+    pub(crate) offset: usize,
+    /// Number of shapes of a leaf, or zero for interior nodes.
+    pub(crate) count: usize,
+    /// Axis along which the children of an interior node are ordered.
+    pub(crate) axis: usize,
+    /// Whether the second child lies before the first one along the axis.
+    pub(crate) descending: bool,
+}
+
+/// Bounding Volume Hierarchy whose nodes are stored depth first in a single array, with the
+/// shapes of its leaves stored contiguously in another.
+#[derive(Debug, Clone, Default)]
+pub struct LinearBvh {
+    /// Nodes of the hierarchy; the root is the first one.
+    nodes: Vec<LinearNode>,
+    /// Shapes, in the order of the leaves that refer to them.
+    shapes: Vec<HittableObjects>,
+    /// Surface area heuristic cost of the hierarchy when it was built.
+    built_cost: f64,
+}
+
+impl LinearBvh {
+    /// Creates a hierarchy over the shapes of a list, splitting them where the surface area
+    /// heuristic estimates the cheapest traversal. Panics if a shape has no bounding box;
+    /// unbounded shapes belong in a World.
+    pub fn new(list: &HittableList, time0: f64, time1: f64) -> Self {
+        let mut items: Vec<BuildItem> = list
+            .objects
+            .iter()
+            .map(|object| BuildItem {
+                bbox: Some(
+                    object
+                        .bounding_box(time0, time1)
+                        .expect("shapes in a linear BVH need a bounding box"),
+                ),
+                object: object.clone(),
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * items.len());
+        if !items.is_empty() {
+            Self::build(&mut nodes, &mut items, 0, 0);
+        }
+        let shapes = items.into_iter().map(|item| item.object).collect();
+        Self::from_parts(nodes, shapes)
+    }
+
+    /// Creates a hierarchy from nodes and shapes that were built before, such as ones read from
+    /// a cache. The nodes must refer to valid ranges of shapes and valid children.
+    pub(crate) fn from_parts(nodes: Vec<LinearNode>, shapes: Vec<HittableObjects>) -> Self {
+        let mut bvh = Self {
+            nodes,
+            shapes,
+            built_cost: 0.0,
+        };
+        bvh.built_cost = bvh.sah_cost();
+        bvh
+    }
+
+    /// Returns the nodes of the hierarchy, in depth first order.
+    pub(crate) fn nodes(&self) -> &[LinearNode] {
+        &self.nodes
+    }
+
+    /// Returns the shapes in the order of the leaves.
+    pub(crate) fn shapes(&self) -> &[HittableObjects] {
+        &self.shapes
+    }
+
+    /// Appends the node over the items, whose first shape has index 'first', and its
+    /// descendants, and returns the box of the node.
+    fn build(
+        nodes: &mut Vec<LinearNode>,
+        items: &mut [BuildItem],
+        first: usize,
+        depth: usize,
+    ) -> AaBb {
+        let index = nodes.len();
+        nodes.push(LinearNode {
+            bbox: AaBb::default(),
+            offset: first,
+            count: items.len(),
+            axis: 0,
+            descending: false,
+        });
+
+        if items.len() <= MAX_LEAF_SHAPES {
+            let bbox = items
+                .iter()
+                .filter_map(|item| item.bbox)
+                .reduce(AaBb::surrounding_box)
+                .unwrap_or_default();
+            nodes[index].bbox = bbox;
+            return bbox;
+        }
+
+        let mid = if depth < MEDIAN_SPLIT_DEPTH {
+            sah_split(items)
+        } else {
+            items.len() / 2
+        };
+        let (left_items, right_items) = items.split_at_mut(mid);
+        let left = Self::build(nodes, left_items, first, depth + 1);
+        let second = nodes.len();
+        let right = Self::build(nodes, right_items, first + mid, depth + 1);
+
+        // Order the children along the axis that separates them the most.
+        let gap = right.centroid() - left.centroid();
+        let axis = (0..3)
+            .max_by(|&a, &b| gap[a].abs().total_cmp(&gap[b].abs()))
+            .unwrap_or(0);
+        let bbox = AaBb::surrounding_box(left, right);
+        nodes[index] = LinearNode {
+            bbox,
+            offset: second,
+            count: 0,
+            axis,
+            descending: gap[axis] < 0.0,
+        };
+        bbox
+    }
+
+    /// Returns the number of nodes of the hierarchy.
+    pub fn total_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of shapes in the hierarchy.
+    pub fn total_shapes(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Returns the shapes in the order of the leaves, to move them between frames. The boxes of
+    /// the hierarchy are only updated by a refit or a rebuild.
+    pub fn shapes_mut(&mut self) -> &mut [HittableObjects] {
+        &mut self.shapes
+    }
+
+    /// Updates the boxes of the nodes, from the leaves up, to the current bounding boxes of the
+    /// shapes over a time interval, keeping the structure of the tree. Panics if a shape no
+    /// longer has a bounding box.
+    pub fn refit(&mut self, time0: f64, time1: f64) {
+        // Children are stored after their parents, so a reverse sweep visits them first.
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            let bbox = if node.count > 0 {
+                self.shapes[node.offset..node.offset + node.count]
+                    .iter()
+                    .map(|shape| {
+                        shape
+                            .bounding_box(time0, time1)
+                            .expect("shapes in a linear BVH need a bounding box")
+                    })
+                    .reduce(AaBb::surrounding_box)
+                    .unwrap_or_default()
+            } else {
+                AaBb::surrounding_box(self.nodes[index + 1].bbox, self.nodes[node.offset].bbox)
+            };
+            self.nodes[index].bbox = bbox;
+        }
+    }
+
+    /// Returns the expected cost of tracing a Ray that hits the root box, estimated with the
+    /// surface area heuristic: the chance of visiting each node is the ratio of its area to the
+    /// area of the root.
+    pub fn sah_cost(&self) -> f64 {
+        let Some(root) = self.nodes.first() else {
+            return 0.0;
+        };
+        let root_area = root.bbox.surface_area();
+        if root_area <= 0.0 {
+            return 0.0;
+        }
+        self.nodes
+            .iter()
+            .map(|node| {
+                let cost = if node.count > 0 {
+                    node.count as f64 * INTERSECTION_COST
+                } else {
+                    TRAVERSAL_COST
+                };
+                node.bbox.surface_area() / root_area * cost
+            })
+            .sum()
+    }
+
+    /// Refits the hierarchy to a new time interval, such as the one of the next frame, and
+    /// rebuilds it instead when the refitted cost is more than 'threshold' times the cost it had
+    /// when it was built. Returns whether the hierarchy was rebuilt.
+    pub fn refit_or_rebuild(&mut self, time0: f64, time1: f64, threshold: f64) -> bool {
+        self.refit(time0, time1);
+        if self.sah_cost() <= threshold * self.built_cost {
+            return false;
+        }
+        let list = HittableList {
+            objects: std::mem::take(&mut self.shapes),
+        };
+        *self = Self::new(&list, time0, time1);
+        true
+    }
+}
+
+/// Returns whether a ray, given by its origin and the inverse of its direction, crosses a box
+/// between t_min and t_max.
+fn hit_box(bbox: &AaBb, origin: Point3, inverse: Vec3, t_min: f64, t_max: f64) -> bool {
+    let mut t_min = t_min;
+    let mut t_max = t_max;
+    for axis in 0..3 {
+        let t0 = (bbox.minimum[axis] - origin[axis]) * inverse[axis];
+        let t1 = (bbox.maximum[axis] - origin[axis]) * inverse[axis];
+        let (t0, t1) = if inverse[axis] < 0.0 {
+            (t1, t0)
+        } else {
+            (t0, t1)
+        };
+        t_min = t_min.max(t0);
+        t_max = t_max.min(t1);
+        if t_max < t_min {
+            return false;
+        }
+    }
+    true
+}
+
+impl Accelerator for LinearBvh {
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        stats: &mut TraversalStats,
+    ) -> Option<HitRecord> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let inverse = Vec3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+
+        let mut closest_so_far = t_max;
+        let mut hit_anything = None;
+        let mut stack = [0usize; STACK_SIZE];
+        let mut stack_size = 0;
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            stats.node_visits += 1;
+            if hit_box(&node.bbox, origin, inverse, t_min, closest_so_far) {
+                if node.count > 0 {
+                    stats.primitive_tests += node.count as u64;
+                    for shape in &self.shapes[node.offset..node.offset + node.count] {
+                        if let Some(hit) = shape.hit(ray, t_min, closest_so_far) {
+                            closest_so_far = hit.t;
+                            hit_anything = Some(hit);
+                        }
+                    }
+                } else {
+                    // Visit the child nearer to the origin first and keep the other for later.
+                    let (near, far) = if (direction[node.axis] < 0.0) != node.descending {
+                        (node.offset, current + 1)
+                    } else {
+                        (current + 1, node.offset)
+                    };
+                    stack[stack_size] = far;
+                    stack_size += 1;
+                    current = near;
+                    continue;
+                }
+            }
+            if stack_size == 0 {
+                break;
+            }
+            stack_size -= 1;
+            current = stack[stack_size];
+        }
+        hit_anything
+    }
+}
+
+impl Hittable for LinearBvh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.hit_counted(ray, t_min, t_max, &mut TraversalStats::default())
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        self.nodes.first().map(|root| root.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinearBvh;
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::ray::Ray;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::utilities::random_float_range;
+    use crate::vector::{Point3, Vec3};
+
+    fn random_point(range: f64) -> Point3 {
+        Point3::new(
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+            random_float_range(-range, range),
+        )
+    }
+
+    #[test]
+    fn linear_bvh_matches_brute_force() {
+        let material = HitRecord::default().material;
+        for total in [0, 1, 5, 500] {
+            let mut list = HittableList::default();
+            for _ in 0..total {
+                let radius = random_float_range(0.05, 1.0);
+                list.add(HittableObjects::Sphere(Sphere::new(
+                    random_point(10.0),
+                    radius,
+                    material.clone(),
+                )));
+            }
+            let bvh = LinearBvh::new(&list, 0.0, 1.0);
+            assert_eq!(bvh.total_shapes(), total);
+            assert_eq!(bvh.bounding_box(0.0, 1.0).is_some(), total > 0);
+
+            for _ in 0..2000 {
+                let origin = random_point(15.0);
+                let ray = Ray::new(origin, random_point(10.0) - origin, 0.0);
+                let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                let found = bvh.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                assert_eq!(found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn linear_bvh_refits_and_rebuilds_when_degraded() {
+        let material = HitRecord::default().material;
+        let mut list = HittableList::default();
+        for _ in 0..300 {
+            list.add(HittableObjects::Sphere(Sphere::new(
+                random_point(10.0),
+                0.3,
+                material.clone(),
+            )));
+        }
+        let mut bvh = LinearBvh::new(&list, 0.0, 1.0);
+        let built_cost = bvh.sah_cost();
+
+        let assert_matches_shapes = |bvh: &LinearBvh| {
+            let list = HittableList {
+                objects: bvh.shapes.clone(),
+            };
+            for _ in 0..1000 {
+                let origin = random_point(15.0);
+                let ray = Ray::new(origin, random_point(10.0) - origin, 0.0);
+                let expected = list.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                let found = bvh.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+                assert_eq!(found, expected);
+            }
+        };
+
+        // Small moves keep the hierarchy good enough to refit.
+        for shape in bvh.shapes_mut() {
+            let center = shape.bounding_box(0.0, 1.0).unwrap().centroid();
+            let center = center + Vec3::new(0.2, -0.1, 0.1);
+            *shape = HittableObjects::Sphere(Sphere::new(center, 0.3, material.clone()));
+        }
+        assert!(!bvh.refit_or_rebuild(0.0, 1.0, 1.5));
+        assert_matches_shapes(&bvh);
+
+        // Scattering the spheres makes the refitted boxes overlap, which triggers a rebuild.
+        for shape in bvh.shapes_mut() {
+            *shape =
+                HittableObjects::Sphere(Sphere::new(random_point(10.0), 0.3, material.clone()));
+        }
+        bvh.refit(0.0, 1.0);
+        assert!(bvh.sah_cost() > 1.5 * built_cost);
+        assert_matches_shapes(&bvh);
+        assert!(bvh.refit_or_rebuild(0.0, 1.0, 1.5));
+        assert!(bvh.sah_cost() < 1.5 * built_cost);
+        assert_matches_shapes(&bvh);
+    }
+}
@@ -0,0 +1,25 @@
+//! The Loaders module provides functionality to import shapes, materials and scenes from model
+//! files.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+pub mod gltf;
+pub mod mtl;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+use std::io::{Error, ErrorKind};
+
+/// Returns an InvalidData error about a whole file, such as binary data or missing content.
+pub(crate) fn data_error(file: &str, message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("{}: {}", file, message))
+}
+
+/// Returns an InvalidData error that points to the file and line where parsing failed.
+pub(crate) fn parse_error(file: &str, line: usize, message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("{}:{}: {}", file, line, message),
+    )
+}
@@ -0,0 +1,1104 @@
+//! The glTF 2.0 loader: parses .gltf and .glb files into the meshes, cameras and materials of a
+//! Scene.
+
+use crate::bvh_cache::load_or_build;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::hittable::HittableList;
+use crate::loaders::data_error;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::materials::Materials;
+use crate::shapes::triangle_mesh::{Mesh, MeshFace, TriangleMesh};
+use crate::shapes::HittableObjects;
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::Texture;
+use crate::transform::{Mat4, Quaternion};
+use crate::vector::{Point3, Vec3};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+/// Magic number at the start of binary glTF files ("glTF").
+const GLB_MAGIC: u32 = 0x4654_6C67;
+/// Type of the JSON chunk of binary glTF files ("JSON").
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+/// Type of the binary chunk of binary glTF files ("BIN\0").
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+/// Index of refraction used for transmissive materials without KHR_materials_ior.
+const DEFAULT_IR: f64 = 1.5;
+
+/// The shapes and cameras of a glTF scene.
+#[derive(Debug)]
+pub struct GltfScene {
+    /// One triangle mesh per mesh instance of the scene, with the node transforms applied.
+    pub world: HittableList,
+    /// Perspective cameras of the scene, in the order of the node hierarchy.
+    pub cameras: Vec<GltfCamera>,
+}
+
+/// A perspective camera placed by the node hierarchy of a glTF scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GltfCamera {
+    /// Position of the camera.
+    pub look_from: Point3,
+    /// Point one unit in front of the camera.
+    pub look_at: Point3,
+    /// Up direction of the camera.
+    pub view_up: Vec3,
+    /// Vertical field of view in degrees.
+    pub vfov: f64,
+    /// Aspect ratio stored in the file, if any.
+    pub aspect_ratio: Option<f64>,
+}
+
+impl GltfCamera {
+    /// Creates a pinhole Camera with the given aspect ratio, usually the one of the Image.
+    pub fn to_camera(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.look_from,
+            self.look_at,
+            self.view_up,
+            self.vfov,
+            aspect_ratio,
+            0.0,
+            1.0,
+            0.0,
+            1.0,
+        )
+    }
+}
+
+/// Loads a .gltf or .glb file and returns its default scene. External buffers and images are
+/// loaded relative to the file.
+pub fn load_gltf(path: &Path) -> Result<GltfScene, Error> {
+    let data = fs::read(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_gltf(&data, &path.display().to_string(), base_dir)
+}
+
+/// Loads a .gltf or .glb file like 'load_gltf', but reads the hierarchies of its meshes from the
+/// cache directory, and writes the ones that are missing or out of date to it.
+pub fn load_gltf_cached(path: &Path, cache_dir: &Path) -> Result<GltfScene, Error> {
+    let data = fs::read(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse(
+        &data,
+        &path.display().to_string(),
+        base_dir,
+        Some(cache_dir),
+    )
+}
+
+/// Parses glTF data, either JSON or binary, and returns its default scene. External buffers and
+/// images are loaded relative to the base directory; the file name is only used to report errors.
+pub fn parse_gltf(data: &[u8], file: &str, base_dir: &Path) -> Result<GltfScene, Error> {
+    parse(data, file, base_dir, None)
+}
+
+/// Parses glTF data, with an optional cache directory for the hierarchies of the meshes.
+fn parse(
+    data: &[u8],
+    file: &str,
+    base_dir: &Path,
+    cache_dir: Option<&Path>,
+) -> Result<GltfScene, Error> {
+    let (json, bin) = if data.len() >= 4 && read_u32(data, 0) == GLB_MAGIC {
+        split_glb(data, file)?
+    } else {
+        (data, None)
+    };
+    let document: Document = serde_json::from_slice(json)
+        .map_err(|err| data_error(file, &format!("invalid JSON: {}", err)))?;
+
+    let loader = Loader::new(&document, bin, file, base_dir, cache_dir)?;
+    loader.load_scene()
+}
+
+/// Splits a binary glTF file into its JSON chunk and its optional binary chunk.
+fn split_glb<'a>(data: &'a [u8], file: &str) -> Result<(&'a [u8], Option<&'a [u8]>), Error> {
+    if data.len() < 12 {
+        return Err(data_error(file, "truncated GLB header"));
+    }
+    let version = read_u32(data, 4);
+    if version != 2 {
+        return Err(data_error(
+            file,
+            &format!("unsupported GLB version {}", version),
+        ));
+    }
+    let length = read_u32(data, 8) as usize;
+    if length > data.len() {
+        return Err(data_error(
+            file,
+            &format!(
+                "truncated GLB: expected {} bytes, found {}",
+                length,
+                data.len()
+            ),
+        ));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(data, offset) as usize;
+        let chunk_type = read_u32(data, offset + 4);
+        let start = offset + 8;
+        if start + chunk_length > length {
+            return Err(data_error(file, "truncated GLB chunk"));
+        }
+        chunks.push((chunk_type, &data[start..start + chunk_length]));
+        offset = start + chunk_length;
+    }
+
+    match chunks.as_slice() {
+        [(GLB_JSON_CHUNK, json), rest @ ..] => {
+            let bin = rest
+                .iter()
+                .find(|(chunk_type, _)| *chunk_type == GLB_BIN_CHUNK)
+                .map(|(_, bin)| *bin);
+            Ok((json, bin))
+        }
+        _ => Err(data_error(file, "GLB without a leading JSON chunk")),
+    }
+}
+
+/// Reads a little endian u32 at the offset.
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Decodes standard base64, as used by data URIs.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in text.bytes().filter(|&c| c != b'=') {
+        // Only the bits that are not written out yet are kept.
+        bits = ((bits << 6) | value(c)? as u32) & 0x3FFF;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Builds the Materials, textures and meshes of a document.
+struct Loader<'a> {
+    /// The parsed JSON document.
+    document: &'a Document,
+    /// Name of the file, used to report errors.
+    file: &'a str,
+    /// Contents of the buffers of the document.
+    buffers: Vec<Vec<u8>>,
+    /// Materials of the document, followed by the default material.
+    materials: Vec<Materials>,
+    /// Optional directory that caches the hierarchies of the meshes.
+    cache_dir: Option<&'a Path>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(
+        document: &'a Document,
+        bin: Option<&[u8]>,
+        file: &'a str,
+        base_dir: &Path,
+        cache_dir: Option<&'a Path>,
+    ) -> Result<Self, Error> {
+        let buffers = document
+            .buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| {
+                let data = match &buffer.uri {
+                    Some(uri) => load_uri(uri, file, base_dir)?,
+                    None if index == 0 => bin
+                        .ok_or_else(|| data_error(file, "buffer 0 without a GLB binary chunk"))?
+                        .to_vec(),
+                    None => return Err(data_error(file, &format!("buffer {} without uri", index))),
+                };
+                if data.len() < buffer.byte_length {
+                    return Err(data_error(
+                        file,
+                        &format!(
+                            "truncated buffer {}: expected {} bytes, found {}",
+                            index,
+                            buffer.byte_length,
+                            data.len()
+                        ),
+                    ));
+                }
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut loader = Self {
+            document,
+            file,
+            buffers,
+            materials: Vec::new(),
+            cache_dir,
+        };
+        let mut images: HashMap<usize, ImageTexture> = HashMap::new();
+        for material in document
+            .materials
+            .iter()
+            .chain(std::iter::once(&MaterialDef::default()))
+        {
+            let material = loader.to_material(material, &mut images, base_dir)?;
+            loader.materials.push(material);
+        }
+        Ok(loader)
+    }
+
+    /// Instantiates the meshes and cameras of the default scene.
+    fn load_scene(&self) -> Result<GltfScene, Error> {
+        let roots: Vec<usize> = match (self.document.scene, self.document.scenes.first()) {
+            (Some(scene), _) => self
+                .document
+                .scenes
+                .get(scene)
+                .ok_or_else(|| self.error(&format!("missing scene {}", scene)))?
+                .nodes
+                .clone(),
+            (None, Some(scene)) => scene.nodes.clone(),
+            // Without scenes, every node that is not a child of another one is a root.
+            (None, None) => {
+                let children: Vec<usize> = self
+                    .document
+                    .nodes
+                    .iter()
+                    .flat_map(|node| node.children.iter().copied())
+                    .collect();
+                (0..self.document.nodes.len())
+                    .filter(|node| !children.contains(node))
+                    .collect()
+            }
+        };
+
+        let mut scene = GltfScene {
+            world: HittableList::default(),
+            cameras: Vec::new(),
+        };
+        for root in roots {
+            self.load_node(root, Mat4::identity(), 0, &mut scene)?;
+        }
+        if scene.world.objects.is_empty() {
+            return Err(self.error("scene without triangles"));
+        }
+        Ok(scene)
+    }
+
+    /// Instantiates a node and its children with the transform of their parent.
+    fn load_node(
+        &self,
+        index: usize,
+        parent: Mat4,
+        depth: usize,
+        scene: &mut GltfScene,
+    ) -> Result<(), Error> {
+        // The hierarchy is a forest, so a deeper recursion can only come from a cycle.
+        if depth > self.document.nodes.len() {
+            return Err(self.error("cycle in the node hierarchy"));
+        }
+        let node = self
+            .document
+            .nodes
+            .get(index)
+            .ok_or_else(|| self.error(&format!("missing node {}", index)))?;
+        let matrix = parent * node.local_matrix();
+
+        if let Some(mesh) = node.mesh {
+            if let Some(mesh) = self.load_mesh(mesh, &matrix)? {
+                let mesh = match self.cache_dir {
+                    Some(cache_dir) => load_or_build(mesh, cache_dir)?,
+                    None => TriangleMesh::new(mesh),
+                };
+                scene.world.add(HittableObjects::TriangleMesh(mesh));
+            }
+        }
+        if let Some(camera) = node.camera {
+            let camera = self
+                .document
+                .cameras
+                .get(camera)
+                .ok_or_else(|| self.error(&format!("missing camera {}", camera)))?;
+            // Orthographic cameras have no equivalent in the existing Camera.
+            if let Some(perspective) = &camera.perspective {
+                let look_from = matrix.transform_point(Point3::zeroes());
+                let forward = matrix.transform_vector(Vec3::new(0.0, 0.0, -1.0));
+                scene.cameras.push(GltfCamera {
+                    look_from,
+                    look_at: look_from + forward.to_unit(),
+                    view_up: matrix.transform_vector(Vec3::new(0.0, 1.0, 0.0)),
+                    vfov: perspective.yfov.to_degrees(),
+                    aspect_ratio: perspective.aspect_ratio,
+                });
+            }
+        }
+        for &child in &node.children {
+            self.load_node(child, matrix, depth + 1, scene)?;
+        }
+        Ok(())
+    }
+
+    /// Builds a Mesh in world space from the primitives of a mesh. Returns None when the mesh
+    /// has no triangles.
+    fn load_mesh(&self, index: usize, matrix: &Mat4) -> Result<Option<Mesh>, Error> {
+        let definition = self
+            .document
+            .meshes
+            .get(index)
+            .ok_or_else(|| self.error(&format!("missing mesh {}", index)))?;
+        let normal_matrix = matrix.inverse().map(|inverse| inverse.transpose());
+        let mut mesh = Mesh {
+            materials: self.materials.clone(),
+            ..Mesh::default()
+        };
+        // Vertex colors multiply the base color. Colors are kept per position for the whole mesh,
+        // so primitives without them get white vertices when another primitive has them.
+        let with_colors = definition
+            .primitives
+            .iter()
+            .any(|primitive| primitive.attributes.contains_key("COLOR_0"));
+
+        for primitive in &definition.primitives {
+            let position = primitive
+                .attributes
+                .get("POSITION")
+                .ok_or_else(|| self.error(&format!("mesh {} without POSITION", index)))?;
+            let positions = self.read_accessor(*position, 3)?;
+            let vertex_count = positions.len();
+            let read_attribute =
+                |name: &str, components: usize| match primitive.attributes.get(name) {
+                    Some(&accessor) => {
+                        let values = self.read_accessor(accessor, components)?;
+                        if values.len() != vertex_count {
+                            return Err(self.error(&format!(
+                                "mesh {}: {} has {} values for {} vertices",
+                                index,
+                                name,
+                                values.len(),
+                                vertex_count
+                            )));
+                        }
+                        Ok(Some(values))
+                    }
+                    None => Ok(None),
+                };
+            let normals = read_attribute("NORMAL", 3)?.filter(|_| normal_matrix.is_some());
+            let uvs = read_attribute("TEXCOORD_0", 2)?;
+            let colors = match read_attribute("COLOR_0", 3)? {
+                Some(colors) => Some(colors),
+                None if with_colors => Some(vec![vec![1.0; 3]; vertex_count]),
+                None => None,
+            };
+
+            let base = mesh.positions.len();
+            let normal_base = mesh.normals.len();
+            let uv_base = mesh.uvs.len();
+            mesh.positions.extend(
+                positions
+                    .iter()
+                    .map(|p| matrix.transform_point(Point3::new(p[0], p[1], p[2]))),
+            );
+            if let (Some(normals), Some(normal_matrix)) = (&normals, &normal_matrix) {
+                mesh.normals.extend(normals.iter().map(|n| {
+                    normal_matrix
+                        .transform_vector(Vec3::new(n[0], n[1], n[2]))
+                        .to_unit()
+                }));
+            }
+            // glTF puts the origin of texture coordinates at the top left of images.
+            if let Some(uvs) = &uvs {
+                mesh.uvs.extend(uvs.iter().map(|uv| (uv[0], 1.0 - uv[1])));
+            }
+            if let Some(colors) = &colors {
+                mesh.colors
+                    .extend(colors.iter().map(|c| Color::new(c[0], c[1], c[2])));
+            }
+
+            let indices: Vec<usize> = match primitive.indices {
+                Some(accessor) => self
+                    .read_accessor(accessor, 1)?
+                    .iter()
+                    .map(|index| index[0] as usize)
+                    .collect(),
+                None => (0..vertex_count).collect(),
+            };
+            if let Some(&invalid) = indices.iter().find(|&&index| index >= vertex_count) {
+                return Err(self.error(&format!(
+                    "mesh {}: index {} out of {} vertices",
+                    index, invalid, vertex_count
+                )));
+            }
+            let material = primitive.material.unwrap_or(self.materials.len() - 1);
+            if material >= self.materials.len() - 1 && primitive.material.is_some() {
+                return Err(self.error(&format!("missing material {}", material)));
+            }
+
+            for triangle in triangles(&indices, primitive.mode) {
+                mesh.faces.push(MeshFace {
+                    positions: triangle.map(|i| base + i),
+                    normals: normals.as_ref().map(|_| triangle.map(|i| normal_base + i)),
+                    uvs: uvs.as_ref().map(|_| triangle.map(|i| uv_base + i)),
+                    material,
+                });
+            }
+        }
+
+        Ok((!mesh.faces.is_empty()).then_some(mesh))
+    }
+
+    /// Reads the elements of an accessor as floats, normalizing integers when requested.
+    /// Only the first 'components' values of each element are kept.
+    fn read_accessor(&self, index: usize, components: usize) -> Result<Vec<Vec<f64>>, Error> {
+        let accessor = self
+            .document
+            .accessors
+            .get(index)
+            .ok_or_else(|| self.error(&format!("missing accessor {}", index)))?;
+        let element_components = match accessor.kind.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            kind => {
+                return Err(self.error(&format!("accessor {}: unsupported type '{}'", index, kind)))
+            }
+        };
+        if element_components < components {
+            return Err(self.error(&format!(
+                "accessor {}: expected {} components, found {}",
+                index, components, element_components
+            )));
+        }
+        let component_size = match accessor.component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => {
+                return Err(self.error(&format!(
+                    "accessor {}: unsupported component type {}",
+                    index, other
+                )))
+            }
+        };
+
+        // Accessors without a buffer view are filled with zeros.
+        let view_index = match accessor.buffer_view {
+            Some(view) => view,
+            None => return Ok(vec![vec![0.0; components]; accessor.count]),
+        };
+        let view = self
+            .document
+            .buffer_views
+            .get(view_index)
+            .ok_or_else(|| self.error(&format!("missing buffer view {}", view_index)))?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or_else(|| self.error(&format!("missing buffer {}", view.buffer)))?;
+        let view_data = view
+            .byte_offset
+            .checked_add(view.byte_length)
+            .and_then(|end| buffer.get(view.byte_offset..end))
+            .ok_or_else(|| {
+                self.error(&format!(
+                    "buffer view {} out of bounds of buffer {}",
+                    view_index, view.buffer
+                ))
+            })?;
+
+        let element_size = element_components * component_size;
+        let stride = view.byte_stride.unwrap_or(element_size);
+        let out_of_bounds = || {
+            self.error(&format!(
+                "accessor {}: {} elements out of bounds of buffer view {}",
+                index, accessor.count, view_index
+            ))
+        };
+        if accessor.count > 0 {
+            let end = (accessor.count - 1)
+                .checked_mul(stride)
+                .and_then(|offset| offset.checked_add(accessor.byte_offset))
+                .and_then(|offset| offset.checked_add(element_size))
+                .ok_or_else(out_of_bounds)?;
+            if end > view_data.len() {
+                return Err(out_of_bounds());
+            }
+        }
+
+        let read = |offset: usize| -> f64 {
+            let bytes = &view_data[offset..offset + component_size];
+            let (value, max) = match accessor.component_type {
+                5120 => (bytes[0] as i8 as f64, 127.0),
+                5121 => (bytes[0] as f64, 255.0),
+                5122 => (i16::from_le_bytes([bytes[0], bytes[1]]) as f64, 32767.0),
+                5123 => (u16::from_le_bytes([bytes[0], bytes[1]]) as f64, 65535.0),
+                5125 => (read_u32(bytes, 0) as f64, u32::MAX as f64),
+                _ => (
+                    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                    1.0,
+                ),
+            };
+            if accessor.normalized {
+                (value / max).max(-1.0)
+            } else {
+                value
+            }
+        };
+        (0..accessor.count)
+            .map(|element| {
+                let start = element
+                    .checked_mul(stride)
+                    .and_then(|offset| offset.checked_add(accessor.byte_offset))
+                    .ok_or_else(out_of_bounds)?;
+                Ok((0..components)
+                    .map(|component| read(start + component * component_size))
+                    .collect())
+            })
+            .collect()
+    }
+
+    /// Maps a metallic-roughness material onto the closest Materials variant.
+    fn to_material(
+        &self,
+        material: &MaterialDef,
+        images: &mut HashMap<usize, ImageTexture>,
+        base_dir: &Path,
+    ) -> Result<Materials, Error> {
+        let pbr = &material.pbr_metallic_roughness;
+        let [r, g, b, _] = pbr.base_color_factor;
+        let base_color = Color::new(r, g, b);
+        let extensions = &material.extensions;
+
+        let strength = extensions
+            .emissive_strength
+            .as_ref()
+            .map_or(1.0, |ext| ext.emissive_strength);
+        let [er, eg, eb] = material.emissive_factor;
+        if er.max(eg).max(eb) > 0.0 {
+            let emit = Color::new(er, eg, eb) * strength;
+            return Ok(Materials::DiffuseLights(DiffuseLight::new(
+                Texture::SolidColor(SolidColor::new(emit)),
+            )));
+        }
+        if let Some(transmission) = &extensions.transmission {
+            if transmission.transmission_factor > 0.0 {
+                let ir = extensions.ior.as_ref().map_or(DEFAULT_IR, |ext| ext.ior);
+                return Ok(Materials::Dielectrics(Dielectric::new(ir)));
+            }
+        }
+
+        // The base color is the texture multiplied by the factor, which is white by default.
+        let texture = match &pbr.base_color_texture {
+            Some(reference) => {
+                let image = self.load_texture(reference.index, images, base_dir)?;
+                if base_color == Color::white() {
+                    Texture::Image(image)
+                } else {
+                    Texture::Image(image.tinted(base_color))
+                }
+            }
+            None => Texture::SolidColor(SolidColor::new(base_color)),
+        };
+        if pbr.metallic_factor >= 0.5 {
+            return Ok(Materials::Metals(Metal::textured(
+                texture,
+                pbr.roughness_factor.clamp(0.0, 1.0),
+            )));
+        }
+        Ok(Materials::Lambertians(Lambertian::new(texture)))
+    }
+
+    /// Loads the image of a texture, sharing the images used by several textures.
+    fn load_texture(
+        &self,
+        index: usize,
+        images: &mut HashMap<usize, ImageTexture>,
+        base_dir: &Path,
+    ) -> Result<ImageTexture, Error> {
+        let source = self
+            .document
+            .textures
+            .get(index)
+            .and_then(|texture| texture.source)
+            .ok_or_else(|| self.error(&format!("texture {} without an image", index)))?;
+        if let Some(image) = images.get(&source) {
+            return Ok(image.clone());
+        }
+        let definition = self
+            .document
+            .images
+            .get(source)
+            .ok_or_else(|| self.error(&format!("missing image {}", source)))?;
+
+        let data = match (&definition.uri, definition.buffer_view) {
+            (Some(uri), _) => load_uri(uri, self.file, base_dir)?,
+            (None, Some(view)) => {
+                let view = self
+                    .document
+                    .buffer_views
+                    .get(view)
+                    .ok_or_else(|| self.error(&format!("missing buffer view {}", view)))?;
+                self.buffers
+                    .get(view.buffer)
+                    .zip(view.byte_offset.checked_add(view.byte_length))
+                    .and_then(|(buffer, end)| buffer.get(view.byte_offset..end))
+                    .ok_or_else(|| self.error(&format!("image {} out of bounds", source)))?
+                    .to_vec()
+            }
+            (None, None) => return Err(self.error(&format!("image {} without data", source))),
+        };
+        let image = ImageTexture::decode(&data, &format!("{}: image {}", self.file, source))?;
+        images.insert(source, image.clone());
+        Ok(image)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        data_error(self.file, message)
+    }
+}
+
+/// Loads the contents of a data URI or of a file relative to the base directory.
+fn load_uri(uri: &str, file: &str, base_dir: &Path) -> Result<Vec<u8>, Error> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, payload) = data
+            .split_once(";base64,")
+            .ok_or_else(|| data_error(file, "data URI is not base64 encoded"))?;
+        return decode_base64(payload).ok_or_else(|| data_error(file, "invalid base64 data URI"));
+    }
+    // Relative URIs may escape spaces and other characters.
+    let path =
+        decode_percent(uri).ok_or_else(|| data_error(file, "invalid percent-encoding in URI"))?;
+    fs::read(base_dir.join(&path))
+        .map_err(|err| data_error(file, &format!("cannot load '{}': {}", path, err)))
+}
+
+/// Decodes the %XX escapes of a URI into the bytes they stand for. Returns None if an escape
+/// is not followed by two hexadecimal digits or the result is not UTF-8.
+fn decode_percent(uri: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(uri.len());
+    let mut rest = uri.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the triangles of the vertex indices for the triangle, strip and fan modes. Points
+/// and lines have no surface and produce no triangles.
+fn triangles(indices: &[usize], mode: u32) -> Vec<[usize; 3]> {
+    match mode {
+        4 => indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+        // Every other triangle of a strip is flipped to keep the winding order.
+        5 => (2..indices.len())
+            .map(|i| match i % 2 {
+                0 => [indices[i - 2], indices[i - 1], indices[i]],
+                _ => [indices[i - 1], indices[i - 2], indices[i]],
+            })
+            .collect(),
+        6 => (2..indices.len())
+            .map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The subset of the glTF JSON document used by the loader.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Document {
+    scene: Option<usize>,
+    scenes: Vec<SceneDef>,
+    nodes: Vec<Node>,
+    meshes: Vec<MeshDef>,
+    accessors: Vec<Accessor>,
+    buffer_views: Vec<BufferView>,
+    buffers: Vec<Buffer>,
+    materials: Vec<MaterialDef>,
+    textures: Vec<TextureDef>,
+    images: Vec<ImageDef>,
+    cameras: Vec<CameraDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SceneDef {
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Node {
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    camera: Option<usize>,
+    /// Column-major local transform, used instead of translation, rotation and scale.
+    matrix: Option<[f64; 16]>,
+    translation: Option<[f64; 3]>,
+    /// Unit quaternion as (x, y, z, w).
+    rotation: Option<[f64; 4]>,
+    scale: Option<[f64; 3]>,
+}
+
+impl Node {
+    /// Returns the transform from the space of the node to the space of its parent.
+    fn local_matrix(&self) -> Mat4 {
+        if let Some(values) = self.matrix {
+            let mut m = [[0.0; 4]; 4];
+            for (column, chunk) in values.chunks_exact(4).enumerate() {
+                for (row, value) in chunk.iter().enumerate() {
+                    m[row][column] = *value;
+                }
+            }
+            return Mat4::new(m);
+        }
+
+        let [tx, ty, tz] = self.translation.unwrap_or([0.0; 3]);
+        let [x, y, z, w] = self.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        let [sx, sy, sz] = self.scale.unwrap_or([1.0; 3]);
+        let rotation = Quaternion::new(x, y, z, w).rotation_matrix();
+        Mat4::translation(Vec3::new(tx, ty, tz)) * rotation * Mat4::scaling(Vec3::new(sx, sy, sz))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MeshDef {
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    #[serde(default = "triangles_mode")]
+    mode: u32,
+}
+
+fn triangles_mode() -> u32 {
+    4
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    #[serde(default)]
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Buffer {
+    uri: Option<String>,
+    byte_length: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct MaterialDef {
+    pbr_metallic_roughness: Pbr,
+    emissive_factor: [f64; 3],
+    extensions: MaterialExtensions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Pbr {
+    base_color_factor: [f64; 4],
+    base_color_texture: Option<TextureRef>,
+    metallic_factor: f64,
+    roughness_factor: f64,
+}
+
+impl Default for Pbr {
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0; 4],
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TextureRef {
+    index: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MaterialExtensions {
+    #[serde(rename = "KHR_materials_emissive_strength")]
+    emissive_strength: Option<EmissiveStrength>,
+    #[serde(rename = "KHR_materials_transmission")]
+    transmission: Option<Transmission>,
+    #[serde(rename = "KHR_materials_ior")]
+    ior: Option<Ior>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EmissiveStrength {
+    emissive_strength: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transmission {
+    #[serde(default)]
+    transmission_factor: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ior {
+    ior: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TextureDef {
+    source: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ImageDef {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CameraDef {
+    perspective: Option<Perspective>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Perspective {
+    yfov: f64,
+    aspect_ratio: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base64, decode_percent, parse_gltf};
+    use crate::color::Color;
+    use crate::materials::Materials;
+    use crate::shapes::HittableObjects;
+    use crate::textures::Textures;
+    use crate::vector::Point3;
+    use std::path::Path;
+
+    /// A red triangle at z = -1, moved by its node, and a camera looking down -Z from z = 5.
+    const JSON: &str = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0, 1]}],
+        "nodes": [
+            {"mesh": 0, "translation": [10, 0, 0]},
+            {"camera": 0, "translation": [0, 0, 5]}
+        ],
+        "cameras": [{"type": "perspective", "perspective": {"yfov": 0.5, "znear": 0.1}}],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "material": 0}]}],
+        "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0}}],
+        "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}],
+        "bufferViews": [{"buffer": 0, "byteLength": 36}],
+        "buffers": [{"byteLength": 36}]
+    }"#;
+
+    /// Wraps the JSON document and the vertex positions into a GLB file.
+    fn glb(json: &str) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let mut bin = Vec::new();
+        for value in [0.0f32, 0.0, -1.0, 1.0, 0.0, -1.0, 0.0, 1.0, -1.0] {
+            bin.extend(value.to_le_bytes());
+        }
+
+        let mut data = Vec::new();
+        data.extend(0x4654_6C67u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        data.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        data.extend((json.len() as u32).to_le_bytes());
+        data.extend(0x4E4F_534Au32.to_le_bytes());
+        data.extend(json);
+        data.extend((bin.len() as u32).to_le_bytes());
+        data.extend(0x004E_4942u32.to_le_bytes());
+        data.extend(bin);
+        data
+    }
+
+    #[test]
+    fn gltf_glb_meshes_cameras_and_materials() {
+        let scene = parse_gltf(&glb(JSON), "tri.glb", Path::new("")).unwrap();
+
+        assert_eq!(scene.world.objects.len(), 1);
+        match &scene.world.objects[0] {
+            HittableObjects::TriangleMesh(mesh) => {
+                assert_eq!(mesh.mesh().positions[1].x, 11.0);
+                assert!(matches!(
+                    mesh.mesh().materials[0],
+                    Materials::Lambertians(_)
+                ));
+            }
+            _ => panic!("expected a triangle mesh"),
+        }
+        assert_eq!(scene.cameras.len(), 1);
+        assert_eq!(scene.cameras[0].look_from.z, 5.0);
+        assert_eq!(scene.cameras[0].look_at.z, 4.0);
+        assert!((scene.cameras[0].vfov - 0.5f64.to_degrees()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gltf_base_color_texture_times_factor() {
+        // A yellow 1x1 PPM image, tinted by the base color factor.
+        let json = JSON
+            .replace(
+                r#""baseColorFactor": [1, 0, 0, 1]"#,
+                r#""baseColorFactor": [0.5, 0.25, 1, 1], "baseColorTexture": {"index": 0}"#,
+            )
+            .replace(
+                r#""accessors""#,
+                r#""textures": [{"source": 0}],
+        "images": [{"uri": "data:image/x-portable-pixmap;base64,UDMgMSAxIDI1NSAyNTUgMjU1IDAK"}],
+        "accessors""#,
+            );
+        let scene = parse_gltf(&glb(&json), "tinted.glb", Path::new("")).unwrap();
+
+        let material = match &scene.world.objects[0] {
+            HittableObjects::TriangleMesh(mesh) => mesh.mesh().materials[0].clone(),
+            _ => panic!("expected a triangle mesh"),
+        };
+        match material {
+            Materials::Lambertians(lambertian) => assert_eq!(
+                lambertian.albedo.value(0.5, 0.5, Point3::zeroes()),
+                Color::new(0.5, 0.25, 0.0)
+            ),
+            _ => panic!("expected a Lambertian material"),
+        }
+
+        // Metals keep the texture of their base color too.
+        let json = json.replace(r#""metallicFactor": 0"#, r#""metallicFactor": 1"#);
+        let scene = parse_gltf(&glb(&json), "metal.glb", Path::new("")).unwrap();
+        match &scene.world.objects[0] {
+            HittableObjects::TriangleMesh(mesh) => match &mesh.mesh().materials[0] {
+                Materials::Metals(metal) => assert_eq!(
+                    metal.albedo.value(0.5, 0.5, Point3::zeroes()),
+                    Color::new(0.5, 0.25, 0.0)
+                ),
+                _ => panic!("expected a Metal material"),
+            },
+            _ => panic!("expected a triangle mesh"),
+        }
+    }
+
+    #[test]
+    fn gltf_errors() {
+        let mut data = glb(JSON);
+        data.truncate(data.len() - 4);
+        let err = parse_gltf(&data, "cut.glb", Path::new("")).err().unwrap();
+        assert!(err.to_string().starts_with("cut.glb: truncated GLB"));
+
+        let json = JSON.replace("\"count\": 3", "\"count\": 4");
+        let err = parse_gltf(&glb(&json), "bad.glb", Path::new(""))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("out of bounds"));
+
+        // Offsets near usize::MAX must be rejected, not wrap around or overflow.
+        let huge = usize::MAX.to_string();
+        for json in [
+            JSON.replace(
+                "\"buffer\": 0,",
+                &format!("\"buffer\": 0, \"byteOffset\": {},", huge),
+            ),
+            JSON.replace(
+                "\"count\": 3",
+                &format!("\"count\": 3, \"byteOffset\": {}", huge),
+            ),
+            JSON.replace("\"count\": 3", &format!("\"count\": {}", huge)),
+        ] {
+            let err = parse_gltf(&glb(&json), "huge.glb", Path::new(""))
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("out of bounds"));
+        }
+    }
+
+    #[test]
+    fn gltf_base64_data_uris() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64("AAEC").unwrap(), [0, 1, 2]);
+        assert!(decode_base64("a*b").is_none());
+    }
+
+    #[test]
+    fn gltf_percent_encoded_uris() {
+        assert_eq!(
+            decode_percent("my%20model%2Fr%C3%A9sum%c3%a9.bin").unwrap(),
+            "my model/résumé.bin"
+        );
+        assert_eq!(decode_percent("plain.bin").unwrap(), "plain.bin");
+        assert!(decode_percent("bad%2").is_none());
+        assert!(decode_percent("bad%zz.bin").is_none());
+        assert!(decode_percent("bad%+1.bin").is_none());
+        assert!(decode_percent("bad%FF.bin").is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! The Wavefront MTL loader: parses material libraries referenced by OBJ files and maps each
+//! material onto the closest Materials variant.
+
+use crate::color::Color;
+use crate::loaders::obj::parse_floats;
+use crate::loaders::parse_error;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::metal::Metal;
+use crate::materials::Materials;
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::Texture;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error};
+use std::path::Path;
+
+/// Index of refraction used for transparent materials that do not define a useful 'Ni'.
+const DEFAULT_IR: f64 = 1.5;
+
+/// Properties of an MTL material that are used to pick the closest Materials variant.
+#[derive(Debug, Clone)]
+struct MtlMaterial {
+    /// Diffuse color (Kd).
+    diffuse: Color,
+    /// Specular color (Ks).
+    specular: Color,
+    /// Specular exponent (Ns).
+    shininess: f64,
+    /// Index of refraction (Ni).
+    ir: f64,
+    /// Opacity (d), or one minus the transparency (Tr).
+    dissolve: f64,
+    /// Emitted color (Ke).
+    emission: Color,
+    /// Illumination model (illum).
+    illum: u32,
+    /// Diffuse texture (map_Kd).
+    diffuse_map: Option<ImageTexture>,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        Self {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::black(),
+            shininess: 0.0,
+            ir: 1.0,
+            dissolve: 1.0,
+            emission: Color::black(),
+            illum: 2,
+            diffuse_map: None,
+        }
+    }
+}
+
+impl MtlMaterial {
+    /// Maps the MTL properties onto the closest Materials variant.
+    fn to_material(&self) -> Materials {
+        let max_component = |c: Color| c.r.max(c.g).max(c.b);
+
+        if max_component(self.emission) > 0.0 {
+            Materials::DiffuseLights(DiffuseLight::new(Texture::SolidColor(SolidColor::new(
+                self.emission,
+            ))))
+        } else if self.dissolve < 1.0 || matches!(self.illum, 4 | 6 | 7 | 9) {
+            let ir = if self.ir > 1.0 { self.ir } else { DEFAULT_IR };
+            Materials::Dielectrics(Dielectric::new(ir))
+        } else if self.illum == 3 || max_component(self.specular) > max_component(self.diffuse) {
+            // Approximate the roughness of a Phong lobe with exponent Ns.
+            let fuzz = (2.0 / (self.shininess + 2.0)).sqrt().clamp(0.0, 1.0);
+            Materials::Metals(Metal::new(self.specular, fuzz))
+        } else {
+            let texture = match &self.diffuse_map {
+                Some(image) => Texture::Image(image.clone()),
+                None => Texture::SolidColor(SolidColor::new(self.diffuse)),
+            };
+            Materials::Lambertians(Lambertian::new(texture))
+        }
+    }
+}
+
+/// Loads an MTL file and returns its materials by name.
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, Materials>, Error> {
+    let file = File::open(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_mtl(BufReader::new(file), &path.display().to_string(), base_dir)
+}
+
+/// Parses MTL data and returns its materials by name. Texture maps are loaded relative to the
+/// base directory; the file name is only used to report errors.
+pub fn parse_mtl<R: BufRead>(
+    reader: R,
+    file: &str,
+    base_dir: &Path,
+) -> Result<HashMap<String, Materials>, Error> {
+    let mut parsed: Vec<(String, MtlMaterial)> = Vec::new();
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = idx + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            let name = args.join(" ");
+            if name.is_empty() {
+                return Err(parse_error(file, line_number, "material without a name"));
+            }
+            parsed.push((name, MtlMaterial::default()));
+            continue;
+        }
+        if keyword.starts_with('#') {
+            continue;
+        }
+
+        let current = match parsed.last_mut() {
+            Some((_, material)) => material,
+            None => {
+                return Err(parse_error(
+                    file,
+                    line_number,
+                    &format!("'{}' before any 'newmtl'", keyword),
+                ))
+            }
+        };
+        let color = |args: &[&str]| -> Result<Color, Error> {
+            let [r, g, b] = parse_floats(args, 1, file, line_number)?;
+            // A single value sets all three channels.
+            Ok(if args.len() == 1 {
+                Color::new(r, r, r)
+            } else {
+                Color::new(r, g, b)
+            })
+        };
+        let scalar = |args: &[&str]| -> Result<f64, Error> {
+            Ok(parse_floats(args, 1, file, line_number)?[0])
+        };
+
+        match keyword {
+            "Kd" => current.diffuse = color(&args)?,
+            "Ks" => current.specular = color(&args)?,
+            "Ke" => current.emission = color(&args)?,
+            "Ns" => current.shininess = scalar(&args)?,
+            "Ni" => current.ir = scalar(&args)?,
+            "d" => current.dissolve = scalar(&args)?,
+            "Tr" => current.dissolve = 1.0 - scalar(&args)?,
+            "illum" => current.illum = scalar(&args)? as u32,
+            "map_Kd" => {
+                let rest = line.trim_start()[keyword.len()..].trim();
+                let name = texture_file_name(rest)
+                    .ok_or_else(|| parse_error(file, line_number, "map_Kd without a file"))?;
+                let image = ImageTexture::load(&base_dir.join(name)).map_err(|err| {
+                    parse_error(
+                        file,
+                        line_number,
+                        &format!("cannot load '{}': {}", name, err),
+                    )
+                })?;
+                current.diffuse_map = Some(image);
+            }
+            // Ambient colors and other maps have no equivalent in the existing Materials.
+            _ => {}
+        }
+    }
+
+    Ok(parsed
+        .into_iter()
+        .map(|(name, material)| (name, material.to_material()))
+        .collect())
+}
+
+/// Returns the file name of a texture statement, skipping the options such as '-s 1 1 1' that
+/// come before it. The name is the rest of the line, so it may contain spaces.
+fn texture_file_name(mut rest: &str) -> Option<&str> {
+    let split_token = |text: &str| -> (usize, usize) {
+        let start = text.len() - text.trim_start().len();
+        let end = text[start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |end| start + end);
+        (start, end)
+    };
+    loop {
+        let (start, end) = split_token(rest);
+        let option = &rest[start..end];
+        if !option.starts_with('-') || option.len() < 2 {
+            break;
+        }
+        rest = &rest[end..];
+        // Offsets and scales take up to three numbers, '-mm' two and other options one value.
+        let (min, max) = match option {
+            "-o" | "-s" | "-t" => (1, 3),
+            "-mm" => (2, 2),
+            _ => (1, 1),
+        };
+        for count in 0..max {
+            let (start, end) = split_token(rest);
+            let value = &rest[start..end];
+            if count >= min && value.parse::<f64>().is_err() {
+                break;
+            }
+            rest = &rest[end..];
+        }
+    }
+    let name = rest.trim();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_mtl, texture_file_name};
+    use crate::materials::Materials;
+    use std::path::Path;
+
+    #[test]
+    fn mtl_maps_to_closest_materials() {
+        let data = "newmtl matte\nKd 0.5 0.2 0.1\n\
+                    newmtl chrome\nKd 0 0 0\nKs 0.9 0.9 0.9\nNs 800\n\
+                    newmtl glass\nNi 1.45\nd 0.1\n\
+                    newmtl lamp\nKe 4 4 4\n";
+        let materials = parse_mtl(data.as_bytes(), "test.mtl", Path::new("")).unwrap();
+
+        assert!(matches!(materials["matte"], Materials::Lambertians(_)));
+        assert!(matches!(&materials["chrome"], Materials::Metals(m) if m.fuzz < 0.1));
+        assert!(matches!(materials["glass"], Materials::Dielectrics(d) if d.ir == 1.45));
+        assert!(matches!(materials["lamp"], Materials::DiffuseLights(_)));
+    }
+
+    #[test]
+    fn mtl_errors_report_file_and_line() {
+        let err = parse_mtl("Kd 1 1 1\n".as_bytes(), "bad.mtl", Path::new("")).unwrap_err();
+        assert!(err.to_string().starts_with("bad.mtl:1:"));
+    }
+
+    #[test]
+    fn mtl_texture_names_keep_spaces_after_options() {
+        assert_eq!(texture_file_name("wood.png"), Some("wood.png"));
+        assert_eq!(
+            texture_file_name("-s 2 2 1 -o 0.5 -blendu off my wood.png"),
+            Some("my wood.png")
+        );
+        assert_eq!(
+            texture_file_name("-mm 0 1  old  oak.ppm"),
+            Some("old  oak.ppm")
+        );
+        assert_eq!(texture_file_name("-s 1 1"), None);
+    }
+}
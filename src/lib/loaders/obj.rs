@@ -0,0 +1,297 @@
+//! The Wavefront OBJ loader: parses vertices, normals, texture coordinates, faces and their
+//! material libraries into a TriangleMesh.
+
+use crate::bvh_cache::load_or_build;
+use crate::loaders::mtl::load_mtl;
+use crate::loaders::{data_error, parse_error};
+use crate::materials::Materials;
+use crate::shapes::triangle_mesh::{Mesh, MeshFace, TriangleMesh};
+use crate::vector::{Point3, Vec3};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error};
+use std::path::Path;
+
+/// Loads an OBJ file and returns a TriangleMesh. Faces use the materials of the libraries
+/// referenced by the file, or the default material when they have none.
+pub fn load_obj(path: &Path, default_material: Materials) -> Result<TriangleMesh, Error> {
+    Ok(TriangleMesh::new(read_obj(path, default_material)?))
+}
+
+/// Loads an OBJ file like 'load_obj', but reads the hierarchy of the mesh from the cache
+/// directory, and writes it there when it is missing or out of date.
+pub fn load_obj_cached(
+    path: &Path,
+    default_material: Materials,
+    cache_dir: &Path,
+) -> Result<TriangleMesh, Error> {
+    load_or_build(read_obj(path, default_material)?, cache_dir)
+}
+
+/// Reads the Mesh of an OBJ file, loading material libraries relative to the file.
+fn read_obj(path: &Path, default_material: Materials) -> Result<Mesh, Error> {
+    let file = File::open(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(
+        BufReader::new(file),
+        &path.display().to_string(),
+        base_dir,
+        default_material,
+    )
+}
+
+/// Parses OBJ data into a Mesh. Polygons are triangulated as fans around their first vertex.
+/// Material libraries are loaded relative to the base directory; the file name is only used to
+/// report errors.
+pub fn parse_obj<R: BufRead>(
+    reader: R,
+    file: &str,
+    base_dir: &Path,
+    default_material: Materials,
+) -> Result<Mesh, Error> {
+    let mut mesh = Mesh {
+        materials: vec![default_material],
+        ..Mesh::default()
+    };
+    // Materials of the libraries, and the index in the mesh of the materials already used.
+    let mut library: HashMap<String, Materials> = HashMap::new();
+    let mut used_materials: HashMap<String, usize> = HashMap::new();
+    let mut current_material = 0;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = idx + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats(&args, 3, file, line_number)?;
+                mesh.positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats(&args, 3, file, line_number)?;
+                mesh.normals.push(Vec3::new(x, y, z));
+            }
+            "vt" => {
+                let [u, v, _] = parse_floats(&args, 1, file, line_number)?;
+                mesh.uvs.push((u, v));
+            }
+            "f" => add_polygon(&mut mesh, &args, current_material, file, line_number)?,
+            "mtllib" => {
+                for name in &args {
+                    let materials = load_mtl(&base_dir.join(name)).map_err(|err| {
+                        parse_error(
+                            file,
+                            line_number,
+                            &format!("cannot load '{}': {}", name, err),
+                        )
+                    })?;
+                    library.extend(materials);
+                }
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                // Unknown materials fall back to the default material.
+                current_material = match (used_materials.get(&name), library.get(&name)) {
+                    (Some(&index), _) => index,
+                    (None, Some(material)) => {
+                        mesh.materials.push(material.clone());
+                        used_materials.insert(name, mesh.materials.len() - 1);
+                        mesh.materials.len() - 1
+                    }
+                    (None, None) => 0,
+                };
+            }
+            // Comments, groups and smoothing groups do not change the geometry.
+            _ => {}
+        }
+    }
+
+    if mesh.faces.is_empty() {
+        return Err(data_error(file, "no faces found"));
+    }
+    Ok(mesh)
+}
+
+/// Position, texture coordinate and normal indices of a face corner.
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// Parses the corners of an 'f' statement and adds the polygon to the mesh as a triangle fan.
+pub(crate) fn add_polygon(
+    mesh: &mut Mesh,
+    args: &[&str],
+    material: usize,
+    file: &str,
+    line: usize,
+) -> Result<(), Error> {
+    if args.len() < 3 {
+        return Err(parse_error(file, line, "face with less than 3 vertices"));
+    }
+    let corners = args
+        .iter()
+        .map(|corner| parse_corner(corner, mesh, file, line))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for i in 1..corners.len() - 1 {
+        let [a, b, c] = [corners[0], corners[i], corners[i + 1]];
+        // Normals and UVs are only used when every corner of the triangle has one.
+        mesh.faces.push(MeshFace {
+            positions: [a.0, b.0, c.0],
+            uvs: match (a.1, b.1, c.1) {
+                (Some(ua), Some(ub), Some(uc)) => Some([ua, ub, uc]),
+                _ => None,
+            },
+            normals: match (a.2, b.2, c.2) {
+                (Some(na), Some(nb), Some(nc)) => Some([na, nb, nc]),
+                _ => None,
+            },
+            material,
+        });
+    }
+    Ok(())
+}
+
+/// Parses at least 'required' and at most three floats; missing optional values are zero.
+pub(crate) fn parse_floats(
+    args: &[&str],
+    required: usize,
+    file: &str,
+    line: usize,
+) -> Result<[f64; 3], Error> {
+    if args.len() < required {
+        return Err(parse_error(
+            file,
+            line,
+            &format!("expected {} values, found {}", required, args.len()),
+        ));
+    }
+    let mut values = [0.0; 3];
+    for (value, arg) in values.iter_mut().zip(args.iter()) {
+        *value = arg
+            .parse()
+            .map_err(|_| parse_error(file, line, &format!("invalid number '{}'", arg)))?;
+    }
+    Ok(values)
+}
+
+/// Parses a face corner in the 'v', 'v/vt', 'v//vn' or 'v/vt/vn' forms into zero-based indices.
+fn parse_corner(corner: &str, mesh: &Mesh, file: &str, line: usize) -> Result<Corner, Error> {
+    let mut parts = corner.split('/');
+    let position = parse_index(parts.next(), mesh.positions.len(), corner, file, line)?
+        .ok_or_else(|| parse_error(file, line, &format!("missing vertex in '{}'", corner)))?;
+    let uv = parse_index(parts.next(), mesh.uvs.len(), corner, file, line)?;
+    let normal = parse_index(parts.next(), mesh.normals.len(), corner, file, line)?;
+    Ok((position, uv, normal))
+}
+
+/// Parses a one-based (or negative, relative to the end) OBJ index into a zero-based index.
+fn parse_index(
+    part: Option<&str>,
+    count: usize,
+    corner: &str,
+    file: &str,
+    line: usize,
+) -> Result<Option<usize>, Error> {
+    let part = match part {
+        Some(part) if !part.is_empty() => part,
+        _ => return Ok(None),
+    };
+    let invalid = || parse_error(file, line, &format!("invalid index in '{}'", corner));
+    let index: i64 = part.parse().map_err(|_| invalid())?;
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => count as i64 + i,
+        _ => return Err(invalid()),
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(invalid());
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_obj;
+    use crate::hittable::HitRecord;
+    use std::path::Path;
+
+    const QUAD: &str = "# a unit quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn obj_quad_is_triangulated() {
+        let mesh = parse_obj(
+            QUAD.as_bytes(),
+            "quad.obj",
+            Path::new(""),
+            HitRecord::default().material,
+        )
+        .unwrap();
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[1].positions, [0, 2, 3]);
+        assert_eq!(mesh.faces[1].uvs, Some([0, 2, 3]));
+        assert_eq!(mesh.faces[1].normals, Some([0, 0, 0]));
+    }
+
+    #[test]
+    fn obj_negative_indices() {
+        let data = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n";
+        let mesh = parse_obj(
+            data.as_bytes(),
+            "neg.obj",
+            Path::new(""),
+            HitRecord::default().material,
+        )
+        .unwrap();
+
+        assert_eq!(mesh.faces[0].positions, [0, 1, 2]);
+        assert_eq!(mesh.faces[0].normals, None);
+    }
+
+    #[test]
+    fn obj_errors_report_file_and_line() {
+        let data = "v 0 0 0\nv 1 0 0\nv 0 1 x\n";
+        let err = parse_obj(
+            data.as_bytes(),
+            "bad.obj",
+            Path::new(""),
+            HitRecord::default().material,
+        );
+        assert!(err.unwrap_err().to_string().starts_with("bad.obj:3:"));
+
+        let data = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        let err = parse_obj(
+            data.as_bytes(),
+            "bad.obj",
+            Path::new(""),
+            HitRecord::default().material,
+        );
+        assert!(err.unwrap_err().to_string().starts_with("bad.obj:4:"));
+
+        let data = "v 0 0 0\nv 1 0 0\n";
+        let err = parse_obj(
+            data.as_bytes(),
+            "empty.obj",
+            Path::new(""),
+            HitRecord::default().material,
+        );
+        assert_eq!(err.unwrap_err().to_string(), "empty.obj: no faces found");
+    }
+}
@@ -0,0 +1,555 @@
+//! The PLY loader: parses ASCII and binary polygon files, with optional vertex normals, texture
+//! coordinates and colors, into a TriangleMesh.
+
+use crate::bvh_cache::load_or_build;
+use crate::color::Color;
+use crate::loaders::{data_error, parse_error};
+use crate::materials::Materials;
+use crate::shapes::triangle_mesh::{Mesh, MeshFace, TriangleMesh};
+use crate::vector::Vec3;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+/// Encoding of the body of a PLY file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// Scalar types of PLY properties.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    /// Parses both the original and the sized names of a type.
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Self::Int8,
+            "uchar" | "uint8" => Self::UInt8,
+            "short" | "int16" => Self::Int16,
+            "ushort" | "uint16" => Self::UInt16,
+            "int" | "int32" => Self::Int32,
+            "uint" | "uint32" => Self::UInt32,
+            "float" | "float32" => Self::Float32,
+            "double" | "float64" => Self::Float64,
+            _ => return None,
+        })
+    }
+
+    /// Returns the size in bytes of the type in binary files.
+    fn size(self) -> usize {
+        match self {
+            Self::Int8 | Self::UInt8 => 1,
+            Self::Int16 | Self::UInt16 => 2,
+            Self::Int32 | Self::UInt32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+        }
+    }
+
+    /// Returns the value that maps to full intensity when the type holds a color channel.
+    fn color_scale(self) -> f64 {
+        match self {
+            Self::UInt8 | Self::Int8 => 255.0,
+            Self::UInt16 | Self::Int16 => 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// A property of an element declared in the header.
+#[derive(Debug, Clone, PartialEq)]
+enum Property {
+    /// A single value.
+    Scalar { name: String, ty: ScalarType },
+    /// A list of values preceded by their count.
+    List {
+        name: String,
+        count: ScalarType,
+        item: ScalarType,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar { name, .. } | Property::List { name, .. } => name,
+        }
+    }
+}
+
+/// An element declared in the header, such as 'vertex' or 'face'.
+#[derive(Debug, Clone)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Loads a PLY file and returns a TriangleMesh. Faces use the given material, tinted by the
+/// colors of the vertices when they have them.
+pub fn load_ply(path: &Path, material: Materials) -> Result<TriangleMesh, Error> {
+    let data = fs::read(path)?;
+    let mesh = parse_ply(&data, &path.display().to_string(), material)?;
+    Ok(TriangleMesh::new(mesh))
+}
+
+/// Loads a PLY file like 'load_ply', but reads the hierarchy of the mesh from the cache
+/// directory, and writes it there when it is missing or out of date.
+pub fn load_ply_cached(
+    path: &Path,
+    material: Materials,
+    cache_dir: &Path,
+) -> Result<TriangleMesh, Error> {
+    let data = fs::read(path)?;
+    let mesh = parse_ply(&data, &path.display().to_string(), material)?;
+    load_or_build(mesh, cache_dir)
+}
+
+/// Parses PLY data into a Mesh. Polygons are triangulated as fans around their first vertex and
+/// elements other than 'vertex' and 'face' are skipped. The file name is only used to report
+/// errors.
+pub fn parse_ply(data: &[u8], file: &str, material: Materials) -> Result<Mesh, Error> {
+    let (format, elements, body_start) = parse_header(data, file)?;
+    let mut body = Body::new(format, &data[body_start..], file)?;
+    let mut mesh = Mesh {
+        materials: vec![material],
+        ..Mesh::default()
+    };
+    let mut vertex_count = None;
+
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                read_vertices(element, &mut body, &mut mesh)?;
+                vertex_count = Some(element.count);
+            }
+            "face" => {
+                let count = vertex_count
+                    .ok_or_else(|| data_error(file, "faces declared before the vertices"))?;
+                read_faces(element, count, &mut body, &mut mesh)?;
+            }
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        body.read_property(property, &element.name)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if mesh.faces.is_empty() {
+        return Err(data_error(file, "no faces found"));
+    }
+    Ok(mesh)
+}
+
+/// Parses the header and returns the format, the elements and the offset of the body.
+fn parse_header(data: &[u8], file: &str) -> Result<(Format, Vec<Element>, usize), Error> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+    let mut line_number = 0;
+
+    loop {
+        let end = data[offset..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| data_error(file, "header without 'end_header'"))?;
+        let line = std::str::from_utf8(&data[offset..offset + end])
+            .map_err(|_| parse_error(file, line_number + 1, "header is not valid UTF-8"))?;
+        offset += end + 1;
+        line_number += 1;
+        let error = |message: &str| parse_error(file, line_number, message);
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(error("not a PLY file"));
+            }
+            continue;
+        }
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(error(&format!("unknown format '{}'", name))),
+                });
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| error(&format!("invalid element count '{}'", count)))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            ["property", "list", count, item, name] => {
+                let property = Property::List {
+                    name: name.to_string(),
+                    count: scalar_type(count, file, line_number)?,
+                    item: scalar_type(item, file, line_number)?,
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(property);
+            }
+            ["property", ty, name] => {
+                let property = Property::Scalar {
+                    name: name.to_string(),
+                    ty: scalar_type(ty, file, line_number)?,
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(property);
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(&format!("invalid header line '{}'", line.trim()))),
+        }
+    }
+
+    let format = format.ok_or_else(|| data_error(file, "header without 'format'"))?;
+    Ok((format, elements, offset))
+}
+
+/// Parses the name of a scalar type of the header.
+fn scalar_type(name: &str, file: &str, line: usize) -> Result<ScalarType, Error> {
+    ScalarType::parse(name)
+        .ok_or_else(|| parse_error(file, line, &format!("unknown type '{}'", name)))
+}
+
+/// Reads the vertex positions and, when declared, their normals, texture coordinates and colors.
+fn read_vertices(element: &Element, body: &mut Body, mesh: &mut Mesh) -> Result<(), Error> {
+    let index = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|property| names.contains(&property.name()))
+    };
+    let all = |names: &[&[&str]]| {
+        names
+            .iter()
+            .map(|names| index(names))
+            .collect::<Option<Vec<usize>>>()
+    };
+    let position = all(&[&["x"], &["y"], &["z"]])
+        .ok_or_else(|| data_error(body.file, "vertices without x, y and z"))?;
+    let normal = all(&[&["nx"], &["ny"], &["nz"]]);
+    let uv = all(&[
+        &["u", "s", "texture_u", "texture_s"],
+        &["v", "t", "texture_v", "texture_t"],
+    ]);
+    let color = all(&[&["red", "r"], &["green", "g"], &["blue", "b"]]);
+
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(&element.properties) {
+            *value = match property {
+                Property::Scalar { ty, .. } => body.read(*ty, &element.name)?,
+                Property::List { .. } => {
+                    body.read_property(property, &element.name)?;
+                    0.0
+                }
+            };
+        }
+
+        let vec = |indices: &[usize]| {
+            Vec3::new(values[indices[0]], values[indices[1]], values[indices[2]])
+        };
+        mesh.positions.push(vec(&position));
+        if let Some(normal) = &normal {
+            mesh.normals.push(vec(normal));
+        }
+        if let Some(uv) = &uv {
+            mesh.uvs.push((values[uv[0]], values[uv[1]]));
+        }
+        if let Some(color) = &color {
+            let channel = |i: usize| match element.properties[color[i]] {
+                Property::Scalar { ty, .. } => values[color[i]] / ty.color_scale(),
+                Property::List { .. } => 0.0,
+            };
+            mesh.colors
+                .push(Color::new(channel(0), channel(1), channel(2)));
+        }
+    }
+    Ok(())
+}
+
+/// Reads the faces and adds them to the mesh as triangle fans.
+fn read_faces(
+    element: &Element,
+    vertex_count: usize,
+    body: &mut Body,
+    mesh: &mut Mesh,
+) -> Result<(), Error> {
+    let indices_property = element
+        .properties
+        .iter()
+        .position(|property| {
+            matches!(property, Property::List { name, .. }
+                if name == "vertex_indices" || name == "vertex_index")
+        })
+        .ok_or_else(|| data_error(body.file, "faces without a 'vertex_indices' list"))?;
+
+    for face in 0..element.count {
+        let mut indices = Vec::new();
+        for (i, property) in element.properties.iter().enumerate() {
+            let values = body.read_property(property, &element.name)?;
+            if i == indices_property {
+                indices = values;
+            }
+        }
+        if indices.len() < 3 {
+            return Err(data_error(
+                body.file,
+                &format!("face {} has less than 3 vertices", face),
+            ));
+        }
+        let indices = indices
+            .iter()
+            .map(|&index| {
+                // Indices may be stored as floats, which must still be whole numbers.
+                if index >= 0.0 && index.fract() == 0.0 && (index as usize) < vertex_count {
+                    Ok(index as usize)
+                } else {
+                    Err(data_error(
+                        body.file,
+                        &format!("face {} has invalid vertex index {}", face, index),
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for i in 1..indices.len() - 1 {
+            let positions = [indices[0], indices[i], indices[i + 1]];
+            mesh.faces.push(MeshFace {
+                positions,
+                normals: (!mesh.normals.is_empty()).then_some(positions),
+                uvs: (!mesh.uvs.is_empty()).then_some(positions),
+                material: 0,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reader of the values in the body of a PLY file.
+struct Body<'a> {
+    /// Name of the file, used to report errors.
+    file: &'a str,
+    /// Encoding of the body.
+    format: Format,
+    /// Raw bytes of a binary body.
+    data: &'a [u8],
+    /// Offset of the next value of a binary body.
+    offset: usize,
+    /// Whitespace separated values of an ASCII body.
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl<'a> Body<'a> {
+    fn new(format: Format, data: &'a [u8], file: &'a str) -> Result<Self, Error> {
+        let text = match format {
+            Format::Ascii => std::str::from_utf8(data)
+                .map_err(|_| data_error(file, "ASCII body is not valid UTF-8"))?,
+            _ => "",
+        };
+        Ok(Self {
+            file,
+            format,
+            data,
+            offset: 0,
+            tokens: text.split_ascii_whitespace(),
+        })
+    }
+
+    /// Reads the values of a property: one value for scalars, every item for lists.
+    fn read_property(&mut self, property: &Property, element: &str) -> Result<Vec<f64>, Error> {
+        match property {
+            Property::Scalar { ty, .. } => Ok(vec![self.read(*ty, element)?]),
+            Property::List { count, item, .. } => {
+                let count = self.read(*count, element)?;
+                if count < 0.0 {
+                    return Err(data_error(
+                        self.file,
+                        &format!("negative list length in {}", element),
+                    ));
+                }
+                (0..count as usize)
+                    .map(|_| self.read(*item, element))
+                    .collect()
+            }
+        }
+    }
+
+    /// Reads a single value of the given type.
+    fn read(&mut self, ty: ScalarType, element: &str) -> Result<f64, Error> {
+        if self.format == Format::Ascii {
+            let token = self.tokens.next().ok_or_else(|| {
+                data_error(self.file, &format!("unexpected end of data in {}", element))
+            })?;
+            return token
+                .parse()
+                .map_err(|_| data_error(self.file, &format!("invalid number '{}'", token)));
+        }
+
+        let size = ty.size();
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| {
+                data_error(
+                    self.file,
+                    &format!(
+                        "truncated data in {}: expected {} bytes at offset {}, found {}",
+                        element,
+                        size,
+                        self.offset,
+                        self.data.len() - self.offset
+                    ),
+                )
+            })?;
+        self.offset += size;
+
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(bytes);
+        if self.format == Format::BinaryBigEndian {
+            buffer[..size].reverse();
+        }
+        // After the reversal the bytes are always little endian.
+        let [b0, b1, b2, b3, ..] = buffer;
+        Ok(match ty {
+            ScalarType::Int8 => b0 as i8 as f64,
+            ScalarType::UInt8 => b0 as f64,
+            ScalarType::Int16 => i16::from_le_bytes([b0, b1]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([b0, b1]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(buffer),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ply;
+    use crate::color::Color;
+    use crate::hittable::HitRecord;
+
+    const HEADER: &str = "element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+";
+
+    #[test]
+    fn ply_ascii_with_vertex_colors() {
+        let data = format!(
+            "ply\nformat ascii 1.0\ncomment a quad\n{}\
+             0 0 0 255 0 0\n1 0 0 0 255 0\n1 1 0 0 0 255\n0 1 0 255 255 255\n4 0 1 2 3\n",
+            HEADER
+        );
+        let mesh = parse_ply(data.as_bytes(), "quad.ply", HitRecord::default().material).unwrap();
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[1].positions, [0, 2, 3]);
+        assert_eq!(mesh.colors[1], Color::green());
+    }
+
+    /// Builds the binary body of the quad in the given byte order.
+    fn binary_body(big_endian: bool) -> Vec<u8> {
+        let float = |value: f32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let int = |value: i32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let mut body = Vec::new();
+        for [x, y] in [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]] {
+            body.extend(float(x));
+            body.extend(float(y));
+            body.extend(float(0.0));
+            body.extend([10, 20, 30]);
+        }
+        body.push(4);
+        for index in 0..4 {
+            body.extend(int(index));
+        }
+        body
+    }
+
+    #[test]
+    fn ply_binary_both_byte_orders() {
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut data = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+            data.extend(binary_body(big_endian));
+            let mesh = parse_ply(&data, "quad.ply", HitRecord::default().material).unwrap();
+
+            assert_eq!(mesh.positions[2].x, 1.0);
+            assert_eq!(mesh.positions[2].y, 1.0);
+            assert_eq!(mesh.faces.len(), 2);
+        }
+    }
+
+    #[test]
+    fn ply_errors() {
+        let mut data = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER).into_bytes();
+        data.extend(binary_body(false));
+        data.truncate(data.len() - 3);
+        let err = parse_ply(&data, "cut.ply", HitRecord::default().material).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("cut.ply: truncated data in face"));
+
+        let data = "ply\nformat ascii 1.0\nelement vertex 3\nproperty quad x\nend_header\n";
+        let err = parse_ply(data.as_bytes(), "bad.ply", HitRecord::default().material);
+        assert!(err.unwrap_err().to_string().starts_with("bad.ply:4:"));
+
+        let data = format!(
+            "ply\nformat ascii 1.0\n{}0 0 0 0 0 0\n1 0 0 0 0 0\n1 1 0 0 0 0\n0 1 0 0 0 0\n3 0 1.5 2\n",
+            HEADER.replace("list uchar int", "list uchar float")
+        );
+        let err = parse_ply(data.as_bytes(), "float.ply", HitRecord::default().material);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "float.ply: face 0 has invalid vertex index 1.5"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! The STL loader: parses binary and ASCII stereolithography files into a TriangleMesh.
+
+use crate::bvh_cache::load_or_build;
+use crate::loaders::{data_error, parse_error};
+use crate::materials::Materials;
+use crate::shapes::triangle_mesh::{Mesh, MeshFace, TriangleMesh};
+use crate::vector::Point3;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+/// Size of the header of a binary STL file.
+const HEADER_SIZE: usize = 80;
+/// Size of a triangle in a binary STL file: normal, three vertices and the attribute count.
+const TRIANGLE_SIZE: usize = 50;
+
+/// Loads an STL file and returns a TriangleMesh whose faces all use the given material.
+pub fn load_stl(path: &Path, material: Materials) -> Result<TriangleMesh, Error> {
+    let data = fs::read(path)?;
+    let mesh = parse_stl(&data, &path.display().to_string(), material)?;
+    Ok(TriangleMesh::new(mesh))
+}
+
+/// Loads an STL file like 'load_stl', but reads the hierarchy of the mesh from the cache
+/// directory, and writes it there when it is missing or out of date.
+pub fn load_stl_cached(
+    path: &Path,
+    material: Materials,
+    cache_dir: &Path,
+) -> Result<TriangleMesh, Error> {
+    let data = fs::read(path)?;
+    let mesh = parse_stl(&data, &path.display().to_string(), material)?;
+    load_or_build(mesh, cache_dir)
+}
+
+/// Parses binary or ASCII STL data into a Mesh. The stored facet normals are ignored, and
+/// vertices shared by several facets are merged. The file name is only used to report errors.
+pub fn parse_stl(data: &[u8], file: &str, material: Materials) -> Result<Mesh, Error> {
+    let mut builder = StlBuilder::new(material);
+    // Binary files may also start with 'solid', so the size of the payload decides the format.
+    if data.starts_with(b"solid") && !is_binary(data) {
+        let text = std::str::from_utf8(data)
+            .map_err(|_| data_error(file, "ASCII STL is not valid UTF-8"))?;
+        parse_ascii(text, file, &mut builder)?;
+    } else {
+        parse_binary(data, file, &mut builder)?;
+    }
+
+    if builder.mesh.faces.is_empty() {
+        return Err(data_error(file, "no facets found"));
+    }
+    Ok(builder.mesh)
+}
+
+/// Returns true when the triangle count of the header matches the size of the data.
+fn is_binary(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE + 4
+        && HEADER_SIZE + 4 + triangle_count(data) * TRIANGLE_SIZE == data.len()
+}
+
+/// Reads the triangle count that follows the header of a binary STL file.
+fn triangle_count(data: &[u8]) -> usize {
+    let bytes = [
+        data[HEADER_SIZE],
+        data[HEADER_SIZE + 1],
+        data[HEADER_SIZE + 2],
+        data[HEADER_SIZE + 3],
+    ];
+    u32::from_le_bytes(bytes) as usize
+}
+
+/// Parses the triangles of a binary STL file.
+fn parse_binary(data: &[u8], file: &str, builder: &mut StlBuilder) -> Result<(), Error> {
+    if data.len() < HEADER_SIZE + 4 {
+        return Err(data_error(
+            file,
+            &format!(
+                "truncated header: expected {} bytes, found {}",
+                HEADER_SIZE + 4,
+                data.len()
+            ),
+        ));
+    }
+    let count = triangle_count(data);
+    let expected = HEADER_SIZE + 4 + count * TRIANGLE_SIZE;
+    if data.len() < expected {
+        return Err(data_error(
+            file,
+            &format!(
+                "truncated data: {} triangles need {} bytes, found {}",
+                count,
+                expected,
+                data.len()
+            ),
+        ));
+    }
+
+    let read_f32 = |offset: usize| {
+        let bytes = [
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ];
+        f32::from_le_bytes(bytes) as f64
+    };
+    for triangle in 0..count {
+        // Skip the facet normal at the start of each triangle.
+        let start = HEADER_SIZE + 4 + triangle * TRIANGLE_SIZE + 12;
+        let mut vertices = [Point3::zeroes(); 3];
+        for (i, vertex) in vertices.iter_mut().enumerate() {
+            let offset = start + i * 12;
+            *vertex = Point3::new(read_f32(offset), read_f32(offset + 4), read_f32(offset + 8));
+        }
+        builder.add_facet(vertices);
+    }
+    Ok(())
+}
+
+/// Parses the facets of an ASCII STL file.
+fn parse_ascii(text: &str, file: &str, builder: &mut StlBuilder) -> Result<(), Error> {
+    let mut vertices: Vec<Point3> = Vec::with_capacity(3);
+    let mut in_loop = false;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_number = idx + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        match keyword {
+            "outer" => {
+                if in_loop {
+                    return Err(parse_error(file, line_number, "nested 'outer loop'"));
+                }
+                in_loop = true;
+                vertices.clear();
+            }
+            "vertex" => {
+                if !in_loop {
+                    return Err(parse_error(file, line_number, "'vertex' outside a loop"));
+                }
+                let coordinates = tokens
+                    .map(|token| {
+                        token.parse::<f64>().map_err(|_| {
+                            parse_error(file, line_number, &format!("invalid number '{}'", token))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if coordinates.len() != 3 {
+                    return Err(parse_error(
+                        file,
+                        line_number,
+                        &format!("expected 3 values, found {}", coordinates.len()),
+                    ));
+                }
+                vertices.push(Point3::new(coordinates[0], coordinates[1], coordinates[2]));
+            }
+            "endloop" => {
+                if vertices.len() != 3 {
+                    return Err(parse_error(
+                        file,
+                        line_number,
+                        &format!("facet with {} vertices", vertices.len()),
+                    ));
+                }
+                in_loop = false;
+                builder.add_facet([vertices[0], vertices[1], vertices[2]]);
+            }
+            "solid" | "facet" | "endfacet" | "endsolid" => {}
+            _ => {
+                return Err(parse_error(
+                    file,
+                    line_number,
+                    &format!("unknown keyword '{}'", keyword),
+                ))
+            }
+        }
+    }
+
+    if in_loop {
+        return Err(data_error(file, "unexpected end of file inside a facet"));
+    }
+    Ok(())
+}
+
+/// Collects the facets of an STL file into a Mesh, merging identical vertices.
+struct StlBuilder {
+    /// The mesh being built.
+    mesh: Mesh,
+    /// Index of each distinct vertex, keyed by the bits of its coordinates.
+    indices: HashMap<[u64; 3], usize>,
+}
+
+impl StlBuilder {
+    fn new(material: Materials) -> Self {
+        Self {
+            mesh: Mesh {
+                materials: vec![material],
+                ..Mesh::default()
+            },
+            indices: HashMap::new(),
+        }
+    }
+
+    fn add_facet(&mut self, vertices: [Point3; 3]) {
+        let mut positions = [0; 3];
+        for (position, vertex) in positions.iter_mut().zip(vertices) {
+            let key = [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()];
+            let mesh = &mut self.mesh;
+            *position = *self.indices.entry(key).or_insert_with(|| {
+                mesh.positions.push(vertex);
+                mesh.positions.len() - 1
+            });
+        }
+        self.mesh.faces.push(MeshFace {
+            positions,
+            normals: None,
+            uvs: None,
+            material: 0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_stl;
+    use crate::hittable::HitRecord;
+
+    /// Builds a binary STL file with the given triangles.
+    fn binary_stl(triangles: &[[f32; 9]]) -> Vec<u8> {
+        let mut data = vec![0u8; 80];
+        data.extend((triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            data.extend([0u8; 12]);
+            for value in triangle {
+                data.extend(value.to_le_bytes());
+            }
+            data.extend([0u8; 2]);
+        }
+        data
+    }
+
+    #[test]
+    fn stl_binary_merges_shared_vertices() {
+        let data = binary_stl(&[
+            [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+        ]);
+        let mesh = parse_stl(&data, "quad.stl", HitRecord::default().material).unwrap();
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.faces[1].positions, [0, 2, 3]);
+    }
+
+    #[test]
+    fn stl_ascii_facets() {
+        let data = "solid tri\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n\
+                    vertex 0 1 0\nendloop\nendfacet\nendsolid tri\n";
+        let mesh = parse_stl(data.as_bytes(), "tri.stl", HitRecord::default().material).unwrap();
+
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.positions.len(), 3);
+    }
+
+    #[test]
+    fn stl_truncated_binary_is_an_error() {
+        let mut data = binary_stl(&[[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]]);
+        data.truncate(data.len() - 10);
+        let err = parse_stl(&data, "cut.stl", HitRecord::default().material).unwrap_err();
+
+        assert!(err.to_string().starts_with("cut.stl: truncated data"));
+    }
+}
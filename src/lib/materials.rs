@@ -3,13 +3,19 @@
 #![warn(missing_docs, missing_debug_implementations)]
 
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
 
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::materials::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
+use crate::materials::{
+    dielectric::Dielectric, diffuse_light::DiffuseLight, isotropic::Isotropic,
+    lambertian::Lambertian, metal::Metal,
+};
 use crate::ray::Ray;
+use crate::vector::Point3;
 
 /// Materials that can be applied to a Shape.
 #[derive(Debug, Clone)]
@@ -20,6 +26,10 @@ pub enum Materials {
     Dielectrics(Dielectric),
     /// Lambertian materials.
     Lambertians(Lambertian),
+    /// Diffuse light (emissive) materials.
+    DiffuseLights(DiffuseLight),
+    /// Isotropic materials, the phase function of participating media.
+    Isotropics(Isotropic),
 }
 
 /// The Material trait.
@@ -33,6 +43,12 @@ pub trait Scatterable {
         attenuation: &mut Color,
         scattered: &mut Ray,
     ) -> bool;
+
+    /// Function that returns the light emitted by the material at the u,v coordinates and point p.
+    /// Materials that do not emit light return black.
+    fn emitted(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        Color::black()
+    }
 }
 
 impl Scatterable for Materials {
@@ -43,10 +59,24 @@ impl Scatterable for Materials {
         attenuation: &mut Color,
         scattered: &mut Ray,
     ) -> bool {
-        match self {
+        let scatters = match self {
             Materials::Lambertians(lamb) => lamb.scatter(r_in, rec, attenuation, scattered),
             Materials::Metals(metal) => metal.scatter(r_in, rec, attenuation, scattered),
             Materials::Dielectrics(die) => die.scatter(r_in, rec, attenuation, scattered),
+            Materials::DiffuseLights(light) => light.scatter(r_in, rec, attenuation, scattered),
+            Materials::Isotropics(iso) => iso.scatter(r_in, rec, attenuation, scattered),
+        };
+        *attenuation = *attenuation * rec.tint;
+        scatters
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color {
+        match self {
+            Materials::Lambertians(lamb) => lamb.emitted(u, v, p),
+            Materials::Metals(metal) => metal.emitted(u, v, p),
+            Materials::Dielectrics(die) => die.emitted(u, v, p),
+            Materials::DiffuseLights(light) => light.emitted(u, v, p),
+            Materials::Isotropics(iso) => iso.emitted(u, v, p),
         }
     }
 }
@@ -0,0 +1,39 @@
+//! This module defines the Diffuse Light Material and its implementation of the Material trait.
+
+#![warn(missing_docs)]
+
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::materials::Scatterable;
+use crate::ray::Ray;
+use crate::textures::{Texture, Textures};
+use crate::vector::Point3;
+
+/// The Diffuse Light type with the emit property.
+#[derive(Clone, Debug)]
+pub struct DiffuseLight {
+    /// Texture that defines the light emitted by the surface.
+    pub emit: Texture,
+}
+impl DiffuseLight {
+    /// Function creates and returns an owned Diffuse Light material.
+    pub fn new(texture: Texture) -> Self {
+        Self { emit: texture }
+    }
+}
+
+impl Scatterable for DiffuseLight {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _attenuation: &mut Color,
+        _scattered: &mut Ray,
+    ) -> bool {
+        false
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color {
+        self.emit.value(u, v, p)
+    }
+}
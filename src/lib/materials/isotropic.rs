@@ -0,0 +1,37 @@
+//! This module defines the Isotropic Material and its implementation of the Material trait.
+
+#![warn(missing_docs)]
+
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::materials::Scatterable;
+use crate::ray::Ray;
+use crate::textures::{Texture, Textures};
+use crate::vector::Vec3;
+
+/// The Isotropic type, a phase function that scatters light uniformly in every direction.
+#[derive(Clone, Debug)]
+pub struct Isotropic {
+    /// Proportion of incident light that is scattered.
+    pub albedo: Texture,
+}
+impl Isotropic {
+    /// Function creates and returns an owned Isotropic material.
+    pub fn new(texture: Texture) -> Self {
+        Self { albedo: texture }
+    }
+}
+
+impl Scatterable for Isotropic {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        attenuation: &mut Color,
+        scattered: &mut Ray,
+    ) -> bool {
+        *scattered = Ray::new(rec.p, Vec3::random_in_unit_sphere(), r_in.time());
+        *attenuation = self.albedo.value(rec.u, rec.v, rec.p);
+        true
+    }
+}
@@ -6,19 +6,26 @@ use crate::color::Color;
 use crate::hittable::HitRecord;
 use crate::materials::Scatterable;
 use crate::ray::Ray;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::{Texture, Textures};
 use crate::vector::Vec3;
 
 /// The Metal material type with the albedo and fuzz properties.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Metal {
     /// Proportion of incident light that is reflected away from the surface.
-    pub albedo: Color,
+    pub albedo: Texture,
     /// Proportion of 'fuzziness' of a reflection.
     pub fuzz: f64,
 }
 impl Metal {
     /// Function creates and returns an owned Metal material.
     pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self::textured(Texture::SolidColor(SolidColor::new(albedo)), fuzz)
+    }
+
+    /// Function creates and returns an owned Metal material whose albedo varies with a texture.
+    pub fn textured(albedo: Texture, fuzz: f64) -> Self {
         Self { albedo, fuzz }
     }
 }
@@ -37,7 +44,7 @@ impl Scatterable for Metal {
             reflected + self.fuzz * Vec3::random_in_unit_sphere(),
             r_in.time(),
         );
-        *attenuation = self.albedo;
+        *attenuation = self.albedo.value(rec.u, rec.v, rec.p);
 
         Vec3::dot(scattered.direction(), rec.normal) > 0.0
     }
@@ -3,16 +3,25 @@
 #![warn(missing_docs, missing_debug_implementations)]
 
 pub mod aabb;
+pub mod accelerator;
+pub mod background;
 pub mod bvh;
+pub mod bvh_cache;
 pub mod camera;
 pub mod color;
+pub mod grid;
 pub mod hittable;
 pub mod image;
+pub mod kd_tree;
+pub mod linear_bvh;
+pub mod loaders;
 pub mod materials;
 pub mod ray;
 pub mod render;
 pub mod scenes;
 pub mod shapes;
 pub mod textures;
+pub mod transform;
 pub mod utilities;
 pub mod vector;
+pub mod world;
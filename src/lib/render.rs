@@ -12,13 +12,15 @@ use std::path::Path;
 use std::sync::Arc;
 use thousands::Separable;
 
+use crate::background::Background;
 use crate::color::Color;
-use crate::hittable::{Hittable, HittableList};
+use crate::hittable::Hittable;
 use crate::materials::Scatterable;
 use crate::ray::Ray;
-use crate::scenes::{scene_random_spheres, scene_two_spheres, Config, Scene};
+use crate::scenes::{Config, Scene};
 use crate::utilities::{random_float, INFINITY};
 use crate::vector::{Point3, Vec3};
+use crate::world::World;
 
 #[derive(Debug, Clone)]
 struct ScanString(String);
@@ -46,7 +48,7 @@ pub fn render() -> Result<File, std::io::Error> {
     let config = Config::load_config()?;
 
     // Generate scene
-    let scene = Scene::generate_scene(&config);
+    let scene = Scene::generate_scene(&config)?;
 
     // Render
     let path = Path::new("image.ppm");
@@ -64,16 +66,26 @@ pub fn render() -> Result<File, std::io::Error> {
 
     println!(
         "\nImage information:\n - W x H: {} x {} px\n - Recursion depth:{}\n - Samples per pixel: {}\n \
-          - Number of shapes: {}\n - Estimated calculations: {}\n - Scene: {}\n\nRendering now:",
+          - Number of shapes: {}\n - Estimated calculations: {}\n - Scene: {}\n - Accelerator: {:?}\n\nRendering now:",
         scene.image.width,
         scene.image.height,
         scene.image.max_depth,
         scene.image.samples_per_pixel,
         scene.world.total_shapes(),
         &est_calculations.separate_with_commas(),
-        scene.rendered_scene_name
+        scene.rendered_scene_name,
+        scene.accelerator
 
     );
+    let mut world = World::with_accelerator(
+        scene.world.clone(),
+        scene.accelerator,
+        scene.camera.time0,
+        scene.camera.time1,
+    );
+    if scene.stats {
+        world.collect_stats();
+    }
     let pb = ProgressBar::new(scene.image.height as u64);
     pb.set_style(
         ProgressStyle::with_template(
@@ -110,7 +122,13 @@ pub fn render() -> Result<File, std::io::Error> {
                                 let v = (jdx as f64 + random_float())
                                     / (scene.image.height as f64 - 1.0);
                                 let ray = scene.camera.get_ray(u, v);
-                                *color + ray_color(&ray, &scene.world, scene.image.max_depth)
+                                *color
+                                    + ray_color(
+                                        &ray,
+                                        &scene.background,
+                                        &world,
+                                        scene.image.max_depth,
+                                    )
                             })
                             .sum::<Color>();
                         // -------------
@@ -125,12 +143,22 @@ pub fn render() -> Result<File, std::io::Error> {
 
     pb_counter.finish();
 
+    if let Some(stats) = world.stats() {
+        println!(
+            "\nTraversal statistics:\n - Rays: {}\n - Node visits per ray: {:.2}\n \
+              - Primitive tests per ray: {:.2}",
+            stats.rays.separate_with_commas(),
+            stats.node_visits_per_ray(),
+            stats.primitive_tests_per_ray()
+        );
+    }
+
     write!(img_file, "{}", render.0)?;
 
     Ok(img_file)
 }
 
-fn ray_color(ray: &Ray, world: &HittableList, depth: usize) -> Color {
+fn ray_color(ray: &Ray, background: &Background, world: &World, depth: usize) -> Color {
     // Recursion base case: if exceeded the ray bounce limit, no more light is gathered
     if depth == 0 {
         return Color::black();
@@ -139,20 +167,20 @@ fn ray_color(ray: &Ray, world: &HittableList, depth: usize) -> Color {
     if let Some(hit_record) = world.hit(ray, 0.001, INFINITY) {
         let mut scattered = Ray::new(Point3::default(), Vec3::default(), 0.0);
         let mut attenuation = Color::black();
+        let emitted = hit_record
+            .material
+            .emitted(hit_record.u, hit_record.v, hit_record.p);
 
         if hit_record
             .material
             .scatter(ray, &hit_record, &mut attenuation, &mut scattered)
         {
-            return attenuation * ray_color(&scattered, world, depth - 1);
+            return emitted + attenuation * ray_color(&scattered, background, world, depth - 1);
         }
-        return Color::black();
+        return emitted;
     }
 
-    let unit_direction = ray.direction().to_unit();
-    let t = 0.5 * (unit_direction.y + 1.0);
-
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+    background.value(ray)
 }
 
 fn write_color_ppm(color: &Color, samples_per_pixel: usize) -> ScanString {
@@ -175,3 +203,47 @@ fn write_color_ppm(color: &Color, samples_per_pixel: usize) -> ScanString {
     );
     ScanString(line)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ray_color;
+    use crate::background::Background;
+    use crate::color::Color;
+    use crate::hittable::HittableList;
+    use crate::materials::diffuse_light::DiffuseLight;
+    use crate::materials::Materials;
+    use crate::ray::Ray;
+    use crate::shapes::xy_rect::XyRect;
+    use crate::shapes::HittableObjects;
+    use crate::textures::solid_color::SolidColor;
+    use crate::textures::Texture;
+    use crate::vector::{Point3, Vec3};
+    use crate::world::World;
+
+    #[test]
+    fn diffuse_light_emits_from_both_faces() {
+        let light = Color::new(4.0, 3.0, 2.0);
+        let mut list = HittableList::default();
+        list.add(HittableObjects::XyRect(XyRect::new(
+            -1.0,
+            1.0,
+            -1.0,
+            1.0,
+            0.0,
+            Materials::DiffuseLights(DiffuseLight::new(Texture::SolidColor(SolidColor::new(
+                light,
+            )))),
+        )));
+        let world = World::new(list, 0.0, 1.0);
+        let background = Background::None;
+
+        // The panel faces +Z, but the light does not scatter and shines from both sides.
+        let front = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert_eq!(ray_color(&front, &background, &world, 5), light);
+        let back = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert_eq!(ray_color(&back, &background, &world, 5), light);
+
+        let miss = Ray::new(Point3::new(3.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert_eq!(ray_color(&miss, &background, &world, 5), Color::black());
+    }
+}
@@ -3,18 +3,26 @@
 #![warn(missing_docs)]
 #![allow(missing_debug_implementations)]
 
+use crate::accelerator::AcceleratorKind;
+use crate::background::Background;
 use crate::camera::Camera;
 use crate::hittable::HittableList;
 use crate::image::Image;
-use crate::utilities::PI;
+use crate::loaders::gltf::{load_gltf, load_gltf_cached};
 use crate::vector::{Point3, Vec3};
 use serde::Deserialize;
 use std::fs::File;
-use std::io::{ErrorKind, Read};
+use std::io::Read;
 use std::path::Path;
 
+/// Defines the Cornell box scene lit by a ceiling light.
+pub mod scene_cornell_box;
+/// Defines the Cornell box scene with blocks of smoke and fog.
+pub mod scene_cornell_smoke;
 /// Defines a random scene of Spheres of different sizes and material.
 pub mod scene_random_spheres;
+/// Defines a scene with a perlin sphere lit by a rectangle light.
+pub mod scene_simple_light;
 /// Defines a scene with two perlin spheres
 pub mod scene_two_perlin_spheres;
 /// Defines a scene with two spheres touching each other.  
@@ -31,51 +39,127 @@ pub struct Scene {
     pub world: HittableList,
     /// Camera for the scene.
     pub camera: Camera,
+    /// Background for the Rays that do not hit any shape.
+    pub background: Background,
     /// The rendered scene
     pub rendered_scene_name: String,
-    /// Collection of function that create scenes.
-    scenes: Vec<CreateWorldFunctions>,
+    /// Acceleration structure the World is built with.
+    pub accelerator: AcceleratorKind,
+    /// Whether traversal statistics are collected and reported.
+    pub stats: bool,
 }
 impl Scene {
-    /// Generates the scene that is returned to the renderer.
-    pub fn generate_scene(config: &Config) -> Scene {
-        let image = Self::create_image(&config);
-        let camera = Self::set_camera(image.aspect_ratio);
+    /// Generates the scene that is returned to the renderer. Fails if the config names an
+    /// unknown scene, or if the model of the gltf scene is missing or cannot be loaded.
+    pub fn generate_scene(config: &Config) -> Result<Scene, std::io::Error> {
+        let image = Self::create_image(config);
+        let aspect_ratio = image.aspect_ratio;
         let scenes: Vec<CreateWorldFunctions> = vec![
             Box::new(scene_two_spheres::create_world),
             Box::new(scene_random_spheres::create_world),
             Box::new(scene_two_perlin_spheres::create_world),
+            Box::new(scene_simple_light::create_world),
+            Box::new(scene_cornell_box::create_world),
+            Box::new(scene_cornell_smoke::create_world),
         ];
 
-        let selector = config.scene.clone();
-        let world_creator;
-        let rendered_scene_name;
+        let default_camera = || {
+            let look_from = Point3::new(13.0, 2.0, 3.0);
+            Self::set_camera(aspect_ratio, look_from, Point3::zeroes(), 20.0, 0.1, 10.0)
+        };
 
-        match selector.as_str() {
-            "two spheres" => {
-                world_creator = &scenes[0];
-                rendered_scene_name = "Two Spheres".to_string()
+        let selector = config.scene.clone();
+        let (world, rendered_scene_name, scene_background, camera) = match selector.as_str() {
+            "two spheres" => (
+                Self::create_world(&scenes[0]),
+                "Two Spheres".to_string(),
+                Background::sky(),
+                default_camera(),
+            ),
+            "random spheres" => (
+                Self::create_world(&scenes[1]),
+                "Random Spheres".to_string(),
+                Background::sky(),
+                default_camera(),
+            ),
+            "two perlin spheres" => (
+                Self::create_world(&scenes[2]),
+                "Two Perlin Spheres".to_string(),
+                Background::sky(),
+                default_camera(),
+            ),
+            "simple light" => {
+                let look_from = Point3::new(26.0, 3.0, 6.0);
+                let look_at = Point3::new(0.0, 2.0, 0.0);
+                (
+                    Self::create_world(&scenes[3]),
+                    "Simple Light".to_string(),
+                    Background::None,
+                    Self::set_camera(aspect_ratio, look_from, look_at, 20.0, 0.0, 10.0),
+                )
             }
-
-            "random spheres" => {
-                world_creator = &scenes[1];
-                rendered_scene_name = "Random Spheres".to_string()
+            "cornell box" => {
+                let look_from = Point3::new(278.0, 278.0, -800.0);
+                let look_at = Point3::new(278.0, 278.0, 0.0);
+                (
+                    Self::create_world(&scenes[4]),
+                    "Cornell Box".to_string(),
+                    Background::None,
+                    Self::set_camera(aspect_ratio, look_from, look_at, 40.0, 0.0, 10.0),
+                )
             }
-            "two perlin spheres" => {
-                world_creator = &scenes[2];
-                rendered_scene_name = "Two Perlin Spheres".to_string()
+            "cornell smoke" => {
+                let look_from = Point3::new(278.0, 278.0, -800.0);
+                let look_at = Point3::new(278.0, 278.0, 0.0);
+                (
+                    Self::create_world(&scenes[5]),
+                    "Cornell Smoke".to_string(),
+                    Background::None,
+                    Self::set_camera(aspect_ratio, look_from, look_at, 40.0, 0.0, 10.0),
+                )
             }
-            _ => panic!("wrong scene name in config file"),
-        }
+            "gltf" => {
+                let path = config.model.as_ref().ok_or_else(|| {
+                    std::io::Error::other("the gltf scene needs a model file in config file")
+                })?;
+                let model = match &config.bvh_cache {
+                    Some(cache_dir) => load_gltf_cached(Path::new(path), Path::new(cache_dir)),
+                    None => load_gltf(Path::new(path)),
+                }
+                .map_err(|err| {
+                    std::io::Error::new(err.kind(), format!("cannot load the model: {}", err))
+                })?;
+                // The first camera of the model is used, with the aspect ratio of the Image.
+                let camera = match model.cameras.first() {
+                    Some(camera) => camera.to_camera(aspect_ratio),
+                    None => default_camera(),
+                };
+                (
+                    model.world,
+                    "glTF Model".to_string(),
+                    Background::sky(),
+                    camera,
+                )
+            }
+            _ => {
+                return Err(std::io::Error::other(format!(
+                    "wrong scene name in config file: '{}'",
+                    selector
+                )))
+            }
+        };
+        // The background in the config file takes precedence over the scene's own background.
+        let background = config.background.unwrap_or(scene_background);
 
-        let world = Self::create_world(world_creator);
-        Self {
+        Ok(Self {
             image,
             camera,
+            background,
             world,
             rendered_scene_name,
-            scenes,
-        }
+            accelerator: config.accelerator.unwrap_or_default(),
+            stats: config.stats.unwrap_or(false),
+        })
     }
     fn create_image(config: &Config) -> Image {
         Image::new(
@@ -85,19 +169,21 @@ impl Scene {
             config.depth,
         )
     }
-    fn set_camera(aspect_ratio: f64) -> Camera {
-        let look_from = Point3::new(13.0, 2.0, 3.0);
-        let look_at = Point3::new(0.0, 0.0, 0.0);
+    fn set_camera(
+        aspect_ratio: f64,
+        look_from: Point3,
+        look_at: Point3,
+        vfov: f64,
+        aperture: f64,
+        dist_to_focus: f64,
+    ) -> Camera {
         let vup = Vec3::new(0.0, 1.0, 0.0);
-        let dist_to_focus = 10.0;
-        let aperture = 0.1;
-        let _big_r = (PI / 4.0).cos();
 
         Camera::new(
             look_from,
             look_at,
             vup,
-            20.0,
+            vfov,
             aspect_ratio,
             aperture,
             dist_to_focus,
@@ -106,8 +192,7 @@ impl Scene {
         )
     }
     fn create_world<F: FnOnce() -> HittableList>(configurator: F) -> HittableList {
-        let world = configurator();
-        world
+        configurator()
     }
 }
 
@@ -119,6 +204,11 @@ pub struct Config {
     depth: usize,
     samples: usize,
     scene: String,
+    model: Option<String>,
+    bvh_cache: Option<String>,
+    accelerator: Option<AcceleratorKind>,
+    stats: Option<bool>,
+    background: Option<Background>,
 }
 
 impl Config {
@@ -131,7 +221,53 @@ impl Config {
         let config_toml = toml::from_str(buffer.as_str());
         match config_toml {
             Ok(config) => Ok(config),
-            Err(err) => Err(std::io::Error::new(ErrorKind::Other, err.to_string())),
+            Err(err) => Err(std::io::Error::other(err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Scene};
+    use crate::background::Background;
+    use crate::color::Color;
+
+    fn config(extra: &str) -> Config {
+        let toml = format!(
+            "img_width = 4\nimg_height = 2\ndepth = 1\nsamples = 1\n{}",
+            extra
+        );
+        toml::from_str(&toml).unwrap()
+    }
+
+    #[test]
+    fn scene_errors_are_returned() {
+        let err = Scene::generate_scene(&config("scene = \"gltf\"\n"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("model"));
+
+        let missing = config("scene = \"gltf\"\nmodel = \"no/such/model.glb\"\n");
+        let err = Scene::generate_scene(&missing).err().unwrap();
+        assert!(err.to_string().starts_with("cannot load the model"));
+
+        let err = Scene::generate_scene(&config("scene = \"teapot\"\n"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("teapot"));
+    }
+
+    #[test]
+    fn config_background_overrides_the_scene() {
+        let scene = Scene::generate_scene(&config("scene = \"simple light\"\n")).unwrap();
+        assert!(matches!(scene.background, Background::None));
+
+        let solid =
+            "scene = \"simple light\"\n[background]\ntype = \"solid\"\ncolor = [0.25, 0.5, 1.0]\n";
+        let scene = Scene::generate_scene(&config(solid)).unwrap();
+        match scene.background {
+            Background::Solid { color } => assert_eq!(color, Color::new(0.25, 0.5, 1.0)),
+            _ => panic!("expected a solid background"),
         }
     }
 }
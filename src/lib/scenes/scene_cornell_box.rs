@@ -0,0 +1,94 @@
+#![allow(missing_docs)]
+
+use crate::color::Color;
+use crate::hittable::HittableList;
+use crate::materials::{diffuse_light::DiffuseLight, lambertian::Lambertian, Materials};
+use crate::shapes::cuboid::Cuboid;
+use crate::shapes::rotate::Rotate;
+use crate::shapes::translate::Translate;
+use crate::shapes::xy_rect::XyRect;
+use crate::shapes::xz_rect::XzRect;
+use crate::shapes::yz_rect::YzRect;
+use crate::shapes::HittableObjects;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::Texture;
+use crate::vector::{Point3, Vec3};
+
+pub(crate) fn lambertian(color: Color) -> Materials {
+    Materials::Lambertians(Lambertian::new(Texture::SolidColor(SolidColor::new(color))))
+}
+
+pub(crate) fn diffuse_light(color: Color) -> Materials {
+    Materials::DiffuseLights(DiffuseLight::new(Texture::SolidColor(SolidColor::new(
+        color,
+    ))))
+}
+
+/// Creates the empty room: walls, floor, ceiling and the given ceiling light.
+pub(crate) fn create_room(light: XzRect) -> HittableList {
+    let red = lambertian(Color::new(0.65, 0.05, 0.05));
+    let white = lambertian(Color::new(0.73, 0.73, 0.73));
+    let green = lambertian(Color::new(0.12, 0.45, 0.15));
+
+    let mut world = HittableList::default();
+    world.add(HittableObjects::YzRect(YzRect::new(
+        0.0, 555.0, 0.0, 555.0, 555.0, green,
+    )));
+    world.add(HittableObjects::YzRect(YzRect::new(
+        0.0, 555.0, 0.0, 555.0, 0.0, red,
+    )));
+    world.add(HittableObjects::XzRect(light));
+    world.add(HittableObjects::XzRect(XzRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.add(HittableObjects::XzRect(XzRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.add(HittableObjects::XyRect(XyRect::new(
+        0.0, 555.0, 0.0, 555.0, 555.0, white,
+    )));
+
+    world
+}
+
+/// Creates the tall and the short block, rotated and moved into place.
+pub(crate) fn create_blocks(material: Materials) -> (HittableObjects, HittableObjects) {
+    let box_1 = HittableObjects::Cuboid(Cuboid::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        material.clone(),
+    ));
+    let box_1 = HittableObjects::Rotate(Rotate::y(box_1, 15.0));
+    let box_1 = HittableObjects::Translate(Translate::new(box_1, Vec3::new(265.0, 0.0, 295.0)));
+
+    let box_2 = HittableObjects::Cuboid(Cuboid::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        material,
+    ));
+    let box_2 = HittableObjects::Rotate(Rotate::y(box_2, -18.0));
+    let box_2 = HittableObjects::Translate(Translate::new(box_2, Vec3::new(130.0, 0.0, 65.0)));
+
+    (box_1, box_2)
+}
+
+pub fn create_world() -> HittableList {
+    let light = diffuse_light(Color::new(15.0, 15.0, 15.0));
+    let mut world = create_room(XzRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light));
+
+    let (box_1, box_2) = create_blocks(lambertian(Color::new(0.73, 0.73, 0.73)));
+    world.add(box_1);
+    world.add(box_2);
+
+    world
+}
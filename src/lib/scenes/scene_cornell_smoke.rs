@@ -0,0 +1,30 @@
+#![allow(missing_docs)]
+
+use crate::color::Color;
+use crate::hittable::HittableList;
+use crate::scenes::scene_cornell_box::{create_blocks, create_room, diffuse_light, lambertian};
+use crate::shapes::constant_medium::ConstantMedium;
+use crate::shapes::xz_rect::XzRect;
+use crate::shapes::HittableObjects;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::Texture;
+
+pub fn create_world() -> HittableList {
+    let light = diffuse_light(Color::new(7.0, 7.0, 7.0));
+    let mut world = create_room(XzRect::new(113.0, 443.0, 127.0, 432.0, 554.0, light));
+
+    // Replace the solid blocks with a block of smoke and a block of fog
+    let (box_1, box_2) = create_blocks(lambertian(Color::new(0.73, 0.73, 0.73)));
+    world.add(HittableObjects::ConstantMedium(ConstantMedium::new(
+        box_1,
+        0.01,
+        Texture::SolidColor(SolidColor::new(Color::black())),
+    )));
+    world.add(HittableObjects::ConstantMedium(ConstantMedium::new(
+        box_2,
+        0.01,
+        Texture::SolidColor(SolidColor::new(Color::white())),
+    )));
+
+    world
+}
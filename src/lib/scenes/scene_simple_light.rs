@@ -0,0 +1,43 @@
+#![allow(missing_docs)]
+
+use crate::color::Color;
+use crate::hittable::HittableList;
+use crate::materials::{diffuse_light::DiffuseLight, lambertian::Lambertian, Materials};
+use crate::shapes::plane::Plane;
+use crate::shapes::sphere::Sphere;
+use crate::shapes::xy_rect::XyRect;
+use crate::shapes::HittableObjects;
+use crate::textures::noise::Noise;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::Texture;
+use crate::vector::{Point3, Vec3};
+
+pub fn create_world() -> HittableList {
+    // Create the ground and the sphere
+    let noise = Texture::Noise(Noise::new(4.0));
+    let material_sphere = Materials::Lambertians(Lambertian::new(noise));
+    let ground = HittableObjects::Plane(Plane::new(
+        Point3::zeroes(),
+        Vec3::new(0.0, 1.0, 0.0),
+        material_sphere.clone(),
+    ));
+    let sphere = HittableObjects::Sphere(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        material_sphere,
+    ));
+
+    // Create the light
+    let material_light = Materials::DiffuseLights(DiffuseLight::new(Texture::SolidColor(
+        SolidColor::new(Color::new(4.0, 4.0, 4.0)),
+    )));
+    let light = HittableObjects::XyRect(XyRect::new(3.0, 5.0, 1.0, 3.0, -2.0, material_light));
+
+    // Create the world scene
+    let mut world = HittableList::default();
+    world.add(ground);
+    world.add(sphere);
+    world.add(light);
+
+    world
+}
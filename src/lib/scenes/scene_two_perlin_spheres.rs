@@ -2,22 +2,23 @@
 
 use crate::hittable::HittableList;
 use crate::materials::{lambertian::Lambertian, Materials};
+use crate::shapes::plane::Plane;
 use crate::shapes::sphere::Sphere;
 use crate::shapes::HittableObjects;
 use crate::textures::noise::Noise;
 use crate::textures::Texture;
-use crate::vector::Point3;
+use crate::vector::{Point3, Vec3};
 
 pub fn create_world() -> HittableList {
     // Create the ground
     let noise = Texture::Noise(Noise::new(4.0));
     let material_sphere = Materials::Lambertians(Lambertian::new(noise));
-    let sphere_1 = HittableObjects::Sphere(Sphere::new(
-        Point3::new(20.0, -1000.0, 20.0),
-        1000.0,
+    let ground = HittableObjects::Plane(Plane::new(
+        Point3::new(20.0, 0.0, 20.0),
+        Vec3::new(0.0, 1.0, 0.0),
         material_sphere.clone(),
     ));
-    let sphere_2 = HittableObjects::Sphere(Sphere::new(
+    let sphere = HittableObjects::Sphere(Sphere::new(
         Point3::new(20.0, 2.0, 20.0),
         2.0,
         material_sphere,
@@ -25,8 +26,8 @@ pub fn create_world() -> HittableList {
 
     // Create the world scene
     let mut world = HittableList::default();
-    world.add(sphere_1);
-    world.add(sphere_2);
+    world.add(ground);
+    world.add(sphere);
 
     world
 }
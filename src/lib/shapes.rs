@@ -4,10 +4,31 @@
 
 use crate::aabb::AaBb;
 use crate::bvh::BhvNode;
-use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::{hits_along, HitRecord, Hittable};
 use crate::ray::Ray;
+use crate::shapes::animated::Animated;
+use crate::shapes::cone::Cone;
+use crate::shapes::constant_medium::ConstantMedium;
+use crate::shapes::csg::Csg;
+use crate::shapes::cuboid::Cuboid;
+use crate::shapes::cylinder::Cylinder;
+use crate::shapes::disk::Disk;
+use crate::shapes::heightfield::Heightfield;
+use crate::shapes::instance::{Instance, TopLevel};
 use crate::shapes::moving_sphere::MovingSphere;
+use crate::shapes::plane::Plane;
+use crate::shapes::quad::Quad;
+use crate::shapes::rotate::Rotate;
+use crate::shapes::sdf::Sdf;
 use crate::shapes::sphere::Sphere;
+use crate::shapes::torus::Torus;
+use crate::shapes::transformed::Transformed;
+use crate::shapes::translate::Translate;
+use crate::shapes::triangle::Triangle;
+use crate::shapes::triangle_mesh::{MeshTriangle, TriangleMesh};
+use crate::shapes::xy_rect::XyRect;
+use crate::shapes::xz_rect::XzRect;
+use crate::shapes::yz_rect::YzRect;
 
 /// Module that contains the functionality of the Sphere shape.
 pub mod sphere;
@@ -15,6 +36,73 @@ pub mod sphere;
 /// Module that contains the functionality of a Moving sphere shape.
 pub mod moving_sphere;
 
+/// Module that contains the functionality of the Triangle shape.
+pub mod triangle;
+
+/// Module that contains the functionality of the Triangle Mesh shape.
+pub mod triangle_mesh;
+
+/// Module that contains the functionality of the infinite Plane shape.
+pub mod plane;
+
+/// Module that contains the functionality of the Quad (parallelogram) shape.
+pub mod quad;
+
+/// Module that contains the functionality of the Disk shape.
+pub mod disk;
+
+/// Module that contains the functionality of the Cylinder shape.
+pub mod cylinder;
+
+/// Module that contains the functionality of the Cone shape.
+pub mod cone;
+
+/// Module that contains the functionality of the Torus shape.
+pub mod torus;
+
+/// Module that contains the local frame shared by the shapes with an axis of symmetry.
+pub(crate) mod frame;
+
+/// Module that contains the functionality of the signed distance field shape.
+pub mod sdf;
+
+/// Module that contains the functionality of the Heightfield (terrain) shape.
+pub mod heightfield;
+
+/// Module that contains the functionality of the Cuboid (box) shape.
+pub mod cuboid;
+
+/// Module that contains the functionality of the Constant Medium volume.
+pub mod constant_medium;
+
+/// Module that contains the functionality of the constructive solid geometry node.
+pub mod csg;
+
+/// Module that contains the functionality of the Translate instance.
+pub mod translate;
+
+/// Module that contains the functionality of the Rotate instance.
+pub mod rotate;
+
+/// Module that contains the functionality of the Transformed instance.
+pub mod transformed;
+
+/// Module that contains the functionality of the keyframed Animated instance.
+pub mod animated;
+
+/// Module that contains the functionality of Instances of shared hierarchies and the TopLevel
+/// hierarchy over them.
+pub mod instance;
+
+/// Module that contains the functionality of the axis-aligned rectangle in the XY plane.
+pub mod xy_rect;
+
+/// Module that contains the functionality of the axis-aligned rectangle in the XZ plane.
+pub mod xz_rect;
+
+/// Module that contains the functionality of the axis-aligned rectangle in the YZ plane.
+pub mod yz_rect;
+
 /// Shape types that can be rendered.
 #[derive(Debug, Clone)]
 pub enum HittableObjects {
@@ -24,15 +112,83 @@ pub enum HittableObjects {
     MovingSphere(MovingSphere),
     /// BhvNode
     BhvNode(BhvNode),
+    /// Axis-aligned rectangles in the XY plane.
+    XyRect(XyRect),
+    /// Axis-aligned rectangles in the XZ plane.
+    XzRect(XzRect),
+    /// Axis-aligned rectangles in the YZ plane.
+    YzRect(YzRect),
+    /// Infinite planes, which have no bounding box.
+    Plane(Plane),
+    /// Parallelograms in any orientation.
+    Quad(Quad),
+    /// Flat disks in any orientation.
+    Disk(Disk),
+    /// Cylinders, with or without caps.
+    Cylinder(Cylinder),
+    /// Cones, with or without a cap.
+    Cone(Cone),
+    /// Ring tori.
+    Torus(Torus),
+    /// Shapes defined by signed distance fields.
+    Sdf(Sdf),
+    /// Terrains from a grid of heights.
+    Heightfield(Heightfield),
+    /// Axis-aligned boxes.
+    Cuboid(Cuboid),
+    /// Two shapes combined by union, intersection or difference.
+    Csg(Csg),
+    /// Shapes moved by an offset.
+    Translate(Translate),
+    /// Shapes rotated around an axis.
+    Rotate(Rotate),
+    /// Shapes placed by an arbitrary affine transform.
+    Transformed(Transformed),
+    /// Shapes moved along keyframes over time.
+    Animated(Animated),
+    /// Placements of a shared bottom-level hierarchy.
+    Instance(Instance),
+    /// Hierarchies over Instances.
+    TopLevel(TopLevel),
+    /// Volumes of constant density such as fog or smoke.
+    ConstantMedium(ConstantMedium),
+    /// Shapes of type Triangle.
+    Triangle(Triangle),
+    /// A single triangle of a shared mesh.
+    MeshTriangle(MeshTriangle),
+    /// Triangle meshes with their own hierarchy.
+    TriangleMesh(TriangleMesh),
 }
 
-
 impl Hittable for HittableObjects {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
         match self {
             HittableObjects::Sphere(sphere) => sphere.hit(ray, t_min, t_max),
             HittableObjects::MovingSphere(sphere) => sphere.hit(ray, t_min, t_max),
             HittableObjects::BhvNode(node) => node.hit(ray, t_min, t_max),
+            HittableObjects::XyRect(rect) => rect.hit(ray, t_min, t_max),
+            HittableObjects::XzRect(rect) => rect.hit(ray, t_min, t_max),
+            HittableObjects::YzRect(rect) => rect.hit(ray, t_min, t_max),
+            HittableObjects::Plane(plane) => plane.hit(ray, t_min, t_max),
+            HittableObjects::Quad(quad) => quad.hit(ray, t_min, t_max),
+            HittableObjects::Disk(disk) => disk.hit(ray, t_min, t_max),
+            HittableObjects::Cylinder(cylinder) => cylinder.hit(ray, t_min, t_max),
+            HittableObjects::Cone(cone) => cone.hit(ray, t_min, t_max),
+            HittableObjects::Torus(torus) => torus.hit(ray, t_min, t_max),
+            HittableObjects::Sdf(sdf) => sdf.hit(ray, t_min, t_max),
+            HittableObjects::Heightfield(terrain) => terrain.hit(ray, t_min, t_max),
+            HittableObjects::Cuboid(cuboid) => cuboid.hit(ray, t_min, t_max),
+            HittableObjects::Csg(csg) => csg.hit(ray, t_min, t_max),
+            HittableObjects::Translate(instance) => instance.hit(ray, t_min, t_max),
+            HittableObjects::Rotate(instance) => instance.hit(ray, t_min, t_max),
+            HittableObjects::Transformed(instance) => instance.hit(ray, t_min, t_max),
+            HittableObjects::Animated(instance) => instance.hit(ray, t_min, t_max),
+            HittableObjects::Instance(instance) => instance.hit(ray, t_min, t_max),
+            HittableObjects::TopLevel(top_level) => top_level.hit(ray, t_min, t_max),
+            HittableObjects::ConstantMedium(medium) => medium.hit(ray, t_min, t_max),
+            HittableObjects::Triangle(triangle) => triangle.hit(ray, t_min, t_max),
+            HittableObjects::MeshTriangle(triangle) => triangle.hit(ray, t_min, t_max),
+            HittableObjects::TriangleMesh(mesh) => mesh.hit(ray, t_min, t_max),
         }
     }
 
@@ -41,6 +197,36 @@ impl Hittable for HittableObjects {
             HittableObjects::Sphere(sphere) => sphere.bounding_box(time0, time1),
             HittableObjects::MovingSphere(sphere) => sphere.bounding_box(time0, time1),
             HittableObjects::BhvNode(node) => node.bounding_box(time0, time1),
+            HittableObjects::XyRect(rect) => rect.bounding_box(time0, time1),
+            HittableObjects::XzRect(rect) => rect.bounding_box(time0, time1),
+            HittableObjects::YzRect(rect) => rect.bounding_box(time0, time1),
+            HittableObjects::Plane(plane) => plane.bounding_box(time0, time1),
+            HittableObjects::Quad(quad) => quad.bounding_box(time0, time1),
+            HittableObjects::Disk(disk) => disk.bounding_box(time0, time1),
+            HittableObjects::Cylinder(cylinder) => cylinder.bounding_box(time0, time1),
+            HittableObjects::Cone(cone) => cone.bounding_box(time0, time1),
+            HittableObjects::Torus(torus) => torus.bounding_box(time0, time1),
+            HittableObjects::Sdf(sdf) => sdf.bounding_box(time0, time1),
+            HittableObjects::Heightfield(terrain) => terrain.bounding_box(time0, time1),
+            HittableObjects::Cuboid(cuboid) => cuboid.bounding_box(time0, time1),
+            HittableObjects::Csg(csg) => csg.bounding_box(time0, time1),
+            HittableObjects::Translate(instance) => instance.bounding_box(time0, time1),
+            HittableObjects::Rotate(instance) => instance.bounding_box(time0, time1),
+            HittableObjects::Transformed(instance) => instance.bounding_box(time0, time1),
+            HittableObjects::Animated(instance) => instance.bounding_box(time0, time1),
+            HittableObjects::Instance(instance) => instance.bounding_box(time0, time1),
+            HittableObjects::TopLevel(top_level) => top_level.bounding_box(time0, time1),
+            HittableObjects::ConstantMedium(medium) => medium.bounding_box(time0, time1),
+            HittableObjects::Triangle(triangle) => triangle.bounding_box(time0, time1),
+            HittableObjects::MeshTriangle(triangle) => triangle.bounding_box(time0, time1),
+            HittableObjects::TriangleMesh(mesh) => mesh.bounding_box(time0, time1),
+        }
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        match self {
+            HittableObjects::Csg(csg) => csg.hit_all(ray, t_min, t_max),
+            _ => hits_along(self, ray, t_min, t_max),
         }
     }
 }
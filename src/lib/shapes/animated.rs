@@ -0,0 +1,297 @@
+//! This module defines the Animated instance type, which moves a shape along keyframes over
+//! time, and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::transform::{Mat4, Quaternion, Transform};
+use crate::vector::{Point3, Vec3};
+use std::sync::Arc;
+
+/// Number of poses sampled between consecutive keyframes to bound the motion of the shape.
+const BOX_SAMPLES: usize = 16;
+
+/// Position, rotation and scale of a shape at a point in time.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    /// Time of the keyframe.
+    time: f64,
+    /// Offset of the shape.
+    translation: Vec3,
+    /// Rotation of the shape around its origin.
+    rotation: Quaternion,
+    /// Scale factors of the shape along its axes.
+    scale: Vec3,
+}
+
+impl Keyframe {
+    /// Creates and returns a keyframe. The shape is scaled first, then rotated and then moved.
+    /// A scale factor of zero flattens the shape, which is then never hit.
+    pub fn new(time: f64, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            time,
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Returns the matrix that places the shape in this pose.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::translation(self.translation)
+            * self.rotation.rotation_matrix()
+            * Mat4::scaling(self.scale)
+    }
+
+    /// Returns the Transform that places the shape in this pose, or None if a scale factor is
+    /// zero.
+    pub fn transform(&self) -> Option<Transform> {
+        let scale = self.scale;
+        if scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0 {
+            return None;
+        }
+        // The inverse of T * R * S is S^-1 * R^T * T^-1, where the transpose of the rotation is
+        // the rotation by the conjugate. This is called for every Ray, so the general matrix
+        // inversion is skipped.
+        let inverse = Mat4::scaling(Vec3::new(1.0 / scale.x, 1.0 / scale.y, 1.0 / scale.z))
+            * self.rotation.conjugate().rotation_matrix()
+            * Mat4::translation(-self.translation);
+        Some(Transform::with_inverse(self.matrix(), inverse))
+    }
+}
+
+/// An instance of a shape that moves along keyframes. Positions follow a smooth curve through
+/// the keyframes, rotations turn at constant speed between them and scales change linearly;
+/// before the first and after the last keyframe the shape stays still.
+#[derive(Debug, Clone)]
+pub struct Animated {
+    /// The shape being animated.
+    object: Arc<HittableObjects>,
+    /// Keyframes sorted by time.
+    keyframes: Arc<Vec<Keyframe>>,
+}
+
+impl Animated {
+    /// Creates and returns an owned instance of the shape that follows the keyframes.
+    pub fn new(object: HittableObjects, keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "an animation needs a keyframe");
+        let mut keyframes = keyframes;
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self {
+            object: Arc::new(object),
+            keyframes: Arc::new(keyframes),
+        }
+    }
+
+    /// Returns the interpolated pose of the shape at a time.
+    pub fn pose_at(&self, time: f64) -> Keyframe {
+        let keys = &self.keyframes;
+        let last = keys.len() - 1;
+        if time <= keys[0].time {
+            return keys[0];
+        }
+        if time >= keys[last].time {
+            return keys[last];
+        }
+        // The segment between keys[k] and keys[k + 1] that contains the time.
+        let k = keys.partition_point(|key| key.time <= time) - 1;
+        let (a, b) = (keys[k], keys[k + 1]);
+        let h = b.time - a.time;
+        let s = (time - a.time) / h;
+
+        // Cubic Hermite curve through the positions, with Catmull-Rom tangents that take the
+        // spacing of the keyframes into account.
+        let tangent = |i: usize| {
+            let (before, after) = (keys[i.saturating_sub(1)], keys[(i + 1).min(last)]);
+            (after.translation - before.translation) / (after.time - before.time)
+        };
+        let (s2, s3) = (s * s, s * s * s);
+        let translation = (2.0 * s3 - 3.0 * s2 + 1.0) * a.translation
+            + (s3 - 2.0 * s2 + s) * h * tangent(k)
+            + (-2.0 * s3 + 3.0 * s2) * b.translation
+            + (s3 - s2) * h * tangent(k + 1);
+
+        Keyframe::new(
+            time,
+            translation,
+            a.rotation.slerp(b.rotation, s),
+            (1.0 - s) * a.scale + s * b.scale,
+        )
+    }
+}
+
+impl Hittable for Animated {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.pose_at(ray.time())
+            .transform()?
+            .hit_through(self.object.as_ref(), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        let object_box = self.object.bounding_box(time0, time1)?;
+        let corners: Vec<Point3> = (0..8)
+            .map(|corner| {
+                let pick = |bit: usize, min: f64, max: f64| {
+                    if corner & bit == 0 {
+                        min
+                    } else {
+                        max
+                    }
+                };
+                Point3::new(
+                    pick(1, object_box.min().x, object_box.max().x),
+                    pick(2, object_box.min().y, object_box.max().y),
+                    pick(4, object_box.min().z, object_box.max().z),
+                )
+            })
+            .collect();
+
+        // Sample the poses over the interval, more densely than the keyframes.
+        let mut breaks = vec![time0, time1];
+        breaks.extend(
+            self.keyframes
+                .iter()
+                .map(|key| key.time)
+                .filter(|&time| time > time0 && time < time1),
+        );
+        breaks.sort_by(f64::total_cmp);
+        let mut times = vec![time0];
+        for pair in breaks.windows(2) {
+            times.extend(
+                (1..=BOX_SAMPLES)
+                    .map(|i| pair[0] + (pair[1] - pair[0]) * i as f64 / BOX_SAMPLES as f64),
+            );
+        }
+
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
+        let mut previous: Option<Vec<Point3>> = None;
+        let mut padding: f64 = 0.0;
+        for time in times {
+            let pose = self.pose_at(time).matrix();
+            let placed: Vec<Point3> = corners.iter().map(|&c| pose.transform_point(c)).collect();
+            for p in &placed {
+                min = Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+                max = Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+            }
+            // Between two samples, a corner strays from the straight line between its positions
+            // by less than half the distance it moves.
+            if let Some(previous) = previous {
+                for (a, b) in previous.iter().zip(&placed) {
+                    padding = padding.max((*b - *a).length() / 2.0);
+                }
+            }
+            previous = Some(placed);
+        }
+        let padding = Vec3::new(padding, padding, padding);
+        Some(AaBb::new(min - padding, max + padding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Animated, Keyframe};
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::transform::Quaternion;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn animated_sphere_follows_keyframes() {
+        let sphere = HittableObjects::Sphere(Sphere::new(
+            Point3::zeroes(),
+            1.0,
+            HitRecord::default().material,
+        ));
+        let key = |time: f64, x: f64, y: f64, scale: f64| {
+            Keyframe::new(
+                time,
+                Vec3::new(x, y, 0.0),
+                Quaternion::identity(),
+                Vec3::new(scale, scale, scale),
+            )
+        };
+        // Up an arc through three keyframes, growing to twice the size.
+        let animated = Animated::new(
+            sphere,
+            vec![
+                key(1.0, 4.0, 0.0, 2.0),
+                key(0.0, -4.0, 0.0, 1.0),
+                key(0.5, 0.0, 3.0, 1.5),
+            ],
+        );
+
+        let origin_at = |time: f64| {
+            let pose = animated.pose_at(time).transform().unwrap();
+            pose.apply_point(Point3::zeroes())
+        };
+        assert_eq!(origin_at(0.5), Point3::new(0.0, 3.0, 0.0));
+        // The path is curved: a quarter of the way along, it is above the straight line.
+        assert!(origin_at(0.25).y > 1.5);
+
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let ray = Ray::new(Point3::new(4.0, 10.0, 0.0), down, 1.0);
+        let hit = animated.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 8.0).abs() < 1e-9);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        let ray = Ray::new(Point3::new(4.0, 10.0, 0.0), down, 0.0);
+        assert!(animated.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        let bbox = animated.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().x <= -5.0 && bbox.max().x >= 6.0);
+        assert!(bbox.max().y >= 4.5 && bbox.min().y <= -2.0);
+        let late = animated.bounding_box(0.9, 1.0).unwrap();
+        assert!(late.min().x > 0.0);
+    }
+
+    #[test]
+    fn keyframe_inverse_matches_the_matrix_inverse() {
+        let key = Keyframe::new(
+            0.0,
+            Vec3::new(1.0, -2.0, 3.0),
+            Quaternion::from_axis_angle(Vec3::new(1.0, 2.0, -1.0), 70.0),
+            Vec3::new(2.0, 0.5, -3.0),
+        );
+        let expected = key.matrix().inverse().unwrap();
+        let inverse = key.transform().unwrap().inverse();
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((inverse.m[row][col] - expected.m[row][col]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn animated_shape_scaled_to_zero_is_not_hit() {
+        let sphere = HittableObjects::Sphere(Sphere::new(
+            Point3::zeroes(),
+            1.0,
+            HitRecord::default().material,
+        ));
+        let key = |time: f64, scale: f64| {
+            Keyframe::new(
+                time,
+                Vec3::zeroes(),
+                Quaternion::identity(),
+                Vec3::new(scale, scale, scale),
+            )
+        };
+        // The sphere shrinks to nothing over a second.
+        let animated = Animated::new(sphere, vec![key(0.0, 1.0), key(1.0, 0.0)]);
+
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let ray = Ray::new(Point3::new(0.0, 10.0, 0.0), down, 0.0);
+        assert!(animated.hit(&ray, 0.001, f64::INFINITY).is_some());
+        let ray = Ray::new(Point3::new(0.0, 10.0, 0.0), down, 1.0);
+        assert!(animated.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        let bbox = animated.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().y <= -1.0 && bbox.max().y >= 1.0);
+    }
+}
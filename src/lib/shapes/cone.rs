@@ -0,0 +1,140 @@
+//! This module defines the Cone type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::frame::{angle_u, Frame};
+use crate::utilities::solve_quadratic;
+use crate::vector::{Point3, Vec3};
+
+/// A circular cone from a base to an apex, optionally closed by a disk at its base.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    /// Frame centered on the base, with the axis of the cone as its Y axis.
+    frame: Frame,
+    /// Radius of the base.
+    radius: f64,
+    /// Distance between the base and the apex.
+    height: f64,
+    /// Whether the base is closed by a disk.
+    capped: bool,
+    /// Material for the cone.
+    material: Materials,
+}
+
+impl Cone {
+    /// Creates and returns an owned cone from the center of its base and its apex. On the side
+    /// the 'u' texture coordinate goes around the axis and 'v' grows from the base to the apex;
+    /// on the cap 'v' grows from the center to the rim.
+    pub fn new(base: Point3, apex: Point3, radius: f64, capped: bool, material: Materials) -> Self {
+        let axis = apex - base;
+        Self {
+            frame: Frame::new(base, axis),
+            radius,
+            height: axis.length(),
+            capped,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (o, d) = self.frame.local_ray(ray);
+        // Closest hit so far as (t, local normal, u, v).
+        let mut closest: Option<(f64, Vec3, f64, f64)> = None;
+        let mut t_max = t_max;
+
+        // The radius shrinks linearly: x^2 + z^2 = (k (height - y))^2.
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let h = self.height - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2.0 * (o.x * d.x + o.z * d.z + k2 * h * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * h * h;
+        for t in solve_quadratic(a, b, c) {
+            let y = o.y + t * d.y;
+            // The quadratic also describes the mirrored cone beyond the apex.
+            if t >= t_min && t <= t_max && (0.0..=self.height).contains(&y) {
+                let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+                let normal = Vec3::new(x, k2 * (self.height - y), z).to_unit();
+                closest = Some((t, normal, angle_u(x, z), y / self.height));
+                t_max = t;
+                break;
+            }
+        }
+
+        if self.capped {
+            let t = -o.y / d.y;
+            if !t.is_nan() && t >= t_min && t <= t_max {
+                let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+                let distance = (x * x + z * z).sqrt();
+                if distance <= self.radius {
+                    let normal = Vec3::new(0.0, -1.0, 0.0);
+                    closest = Some((t, normal, angle_u(x, z), distance / self.radius));
+                }
+            }
+        }
+
+        let (t, normal, u, v) = closest?;
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            u,
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, self.frame.world_vector(normal));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        let r = self.radius;
+        Some(
+            self.frame
+                .bounding_box(Point3::new(-r, 0.0, -r), Point3::new(r, self.height, r)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cone;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn cone_side_and_cap() {
+        // A cone with a 45 degree slope: radius 1 at y = 0, apex at y = 1.
+        let cone = Cone::new(
+            Point3::zeroes(),
+            Point3::new(0.0, 1.0, 0.0),
+            1.0,
+            true,
+            HitRecord::default().material,
+        );
+
+        let side = Ray::new(Point3::new(-5.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = cone.hit(&side, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-9);
+        assert!((hit.normal.x + 0.5f64.sqrt()).abs() < 1e-9);
+        assert!((hit.normal.y - 0.5f64.sqrt()).abs() < 1e-9);
+
+        let below = Ray::new(Point3::new(0.2, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let hit = cone.hit(&below, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-9);
+        assert!((hit.normal.y + 1.0).abs() < 1e-9);
+
+        // Passes above the apex, through the mirrored cone only.
+        let above = Ray::new(Point3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(cone.hit(&above, 0.001, f64::INFINITY).is_none());
+    }
+}
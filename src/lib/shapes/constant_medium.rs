@@ -0,0 +1,132 @@
+//! This module defines the Constant Medium type (fog, smoke) and its implementation of the
+//! Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::isotropic::Isotropic;
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::textures::Texture;
+use crate::utilities::{random_float, INFINITY};
+use crate::vector::Vec3;
+use std::sync::Arc;
+
+/// A volume of constant density bounded by a closed shape.
+#[derive(Debug, Clone)]
+pub struct ConstantMedium {
+    /// Closed shape that bounds the volume.
+    boundary: Arc<HittableObjects>,
+    /// Negative inverse of the density of the volume.
+    neg_inv_density: f64,
+    /// Isotropic material that scatters the Rays inside the volume.
+    phase_function: Materials,
+}
+
+impl ConstantMedium {
+    /// Creates and returns an owned volume with the given density filling the boundary shape.
+    pub fn new(boundary: HittableObjects, density: f64, texture: Texture) -> Self {
+        Self {
+            boundary: Arc::new(boundary),
+            neg_inv_density: -1.0 / density,
+            phase_function: Materials::Isotropics(Isotropic::new(texture)),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Find where the Ray enters and leaves the boundary, also when it starts inside it.
+        let rec_1 = self.boundary.hit(ray, -INFINITY, INFINITY)?;
+        let rec_2 = self.boundary.hit(ray, rec_1.t + 0.0001, INFINITY)?;
+
+        let t_enter = rec_1.t.max(t_min);
+        let t_exit = rec_2.t.min(t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        let t_enter = t_enter.max(0.0);
+
+        // Sample the distance the Ray travels inside the volume before it scatters.
+        let ray_length = ray.direction().length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * random_float().ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        // The normal and front face are arbitrary: a volume has no surface at the scatter point.
+        Some(HitRecord::new(
+            ray.at(t),
+            Vec3::new(1.0, 0.0, 0.0),
+            self.phase_function.clone(),
+            t,
+            0.0,
+            0.0,
+            true,
+        ))
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        self.boundary.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstantMedium;
+    use crate::color::Color;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::materials::Materials;
+    use crate::ray::Ray;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::textures::solid_color::SolidColor;
+    use crate::textures::Texture;
+    use crate::vector::{Point3, Vec3};
+
+    fn fog(density: f64) -> ConstantMedium {
+        let boundary = HittableObjects::Sphere(Sphere::new(
+            Point3::zeroes(),
+            1.0,
+            HitRecord::default().material,
+        ));
+        ConstantMedium::new(
+            boundary,
+            density,
+            Texture::SolidColor(SolidColor::new(Color::white())),
+        )
+    }
+
+    #[test]
+    fn constant_medium_inside_and_outside() {
+        let forward = Vec3::new(0.0, 0.0, 1.0);
+        let outside = Ray::new(Point3::new(0.0, 0.0, -5.0), forward, 0.0);
+        let inside = Ray::new(Point3::zeroes(), forward, 0.0);
+        let behind = Ray::new(Point3::new(0.0, 0.0, 5.0), forward, 0.0);
+
+        // Without density the Rays always cross the volume.
+        let clear = fog(0.0);
+        for _ in 0..100 {
+            assert!(clear.hit(&outside, 0.001, f64::INFINITY).is_none());
+            assert!(clear.hit(&inside, 0.001, f64::INFINITY).is_none());
+        }
+
+        // A dense volume scatters the Rays where they enter it, or right away inside it.
+        let dense = fog(1e12);
+        let hit = dense.hit(&outside, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-6);
+        assert!(matches!(hit.material, Materials::Isotropics(_)));
+        let hit = dense.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 0.001).abs() < 1e-6);
+        assert!(dense.hit(&outside, 0.001, 3.0).is_none());
+        assert!(dense.hit(&behind, 0.001, f64::INFINITY).is_none());
+
+        let bbox = dense.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.max(), Point3::new(1.0, 1.0, 1.0));
+    }
+}
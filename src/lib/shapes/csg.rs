@@ -0,0 +1,223 @@
+//! This module defines the constructive solid geometry (CSG) node, which combines two closed
+//! shapes by union, intersection or difference, and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::utilities::INFINITY;
+use crate::vector::Point3;
+use std::sync::Arc;
+
+/// Boolean operations that combine the insides of two shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// Points inside either shape.
+    Union,
+    /// Points inside both shapes.
+    Intersection,
+    /// Points inside the left shape but not inside the right one.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Returns whether a point is inside the result given whether it is inside each shape.
+    fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Two closed shapes combined by a boolean operation. The shapes must have consistent outward
+/// normals, so that Rays alternately enter and leave them; volumes such as ConstantMedium are
+/// not supported.
+#[derive(Debug, Clone)]
+pub struct Csg {
+    /// The boolean operation.
+    operation: CsgOperation,
+    /// The left operand, which is the shape being cut by a difference.
+    left: Arc<HittableObjects>,
+    /// The right operand, which is the shape removed by a difference.
+    right: Arc<HittableObjects>,
+}
+
+impl Csg {
+    /// Creates and returns an owned combination of two shapes.
+    pub fn new(operation: CsgOperation, left: HittableObjects, right: HittableObjects) -> Self {
+        Self {
+            operation,
+            left: Arc::new(left),
+            right: Arc::new(right),
+        }
+    }
+
+    /// Creates the union of two shapes.
+    pub fn union(left: HittableObjects, right: HittableObjects) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    /// Creates the intersection of two shapes.
+    pub fn intersection(left: HittableObjects, right: HittableObjects) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    /// Creates the left shape minus the right shape.
+    pub fn difference(left: HittableObjects, right: HittableObjects) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+
+    /// Returns the surfaces of the combined shape crossed by the whole line of the Ray, nearest
+    /// first. Each record is flagged as front facing when the Ray enters the combined shape.
+    fn boundaries(&self, ray: &Ray) -> Vec<HitRecord> {
+        let left = self.left.hit_all(ray, -INFINITY, INFINITY);
+        let right = self.right.hit_all(ray, -INFINITY, INFINITY);
+
+        // A line that first leaves a shape starts inside it, e.g. for half-spaces.
+        let starts_inside = |hits: &[HitRecord]| hits.first().is_some_and(|hit| !hit.front_face);
+        let mut in_left = starts_inside(&left);
+        let mut in_right = starts_inside(&right);
+        let mut inside = self.operation.contains(in_left, in_right);
+
+        let mut events: Vec<(HitRecord, bool)> = left
+            .into_iter()
+            .map(|hit| (hit, true))
+            .chain(right.into_iter().map(|hit| (hit, false)))
+            .collect();
+        events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+        let mut boundaries = Vec::new();
+        for (mut hit, is_left) in events {
+            if is_left {
+                in_left = hit.front_face;
+            } else {
+                in_right = hit.front_face;
+            }
+            let now_inside = self.operation.contains(in_left, in_right);
+            if now_inside != inside {
+                // The normal of the record already faces the Ray, so entering or leaving the
+                // result only changes the side. This flips the surfaces of removed shapes.
+                hit.front_face = now_inside;
+                boundaries.push(hit);
+                inside = now_inside;
+            }
+        }
+        boundaries
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.boundaries(ray)
+            .into_iter()
+            .find(|hit| hit.t >= t_min && hit.t <= t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        let left = self.left.bounding_box(time0, time1);
+        let right = self.right.bounding_box(time0, time1);
+        match self.operation {
+            CsgOperation::Union => Some(AaBb::surrounding_box(left?, right?)),
+            CsgOperation::Intersection => match (left, right) {
+                (Some(a), Some(b)) => {
+                    let min = Point3::new(
+                        a.min().x.max(b.min().x),
+                        a.min().y.max(b.min().y),
+                        a.min().z.max(b.min().z),
+                    );
+                    let max = Point3::new(
+                        a.max().x.min(b.max().x),
+                        a.max().y.min(b.max().y),
+                        a.max().z.min(b.max().z),
+                    );
+                    // Disjoint shapes have an empty intersection; keep a valid, tiny box.
+                    let max = Point3::new(max.x.max(min.x), max.y.max(min.y), max.z.max(min.z));
+                    Some(AaBb::new(min, max))
+                }
+                (a, b) => a.or(b),
+            },
+            CsgOperation::Difference => left,
+        }
+    }
+
+    fn hit_all(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        self.boundaries(ray)
+            .into_iter()
+            .filter(|hit| hit.t >= t_min && hit.t <= t_max)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Csg;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::shapes::cuboid::Cuboid;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::vector::{Point3, Vec3};
+
+    fn sphere(x: f64, radius: f64) -> HittableObjects {
+        HittableObjects::Sphere(Sphere::new(
+            Point3::new(x, 0.0, 0.0),
+            radius,
+            HitRecord::default().material,
+        ))
+    }
+
+    /// Returns the distances and front faces of the boundaries along the X axis from x = -5.
+    fn boundaries(csg: &Csg) -> Vec<(f64, bool)> {
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        csg.hit_all(&ray, 0.001, f64::INFINITY)
+            .iter()
+            .map(|hit| ((hit.t * 1e6).round() / 1e6, hit.front_face))
+            .collect()
+    }
+
+    #[test]
+    fn csg_operations_on_overlapping_spheres() {
+        // Spheres over [-2, 2] and [1, 3] along the X axis.
+        let union = Csg::union(sphere(0.0, 2.0), sphere(2.0, 1.0));
+        assert_eq!(boundaries(&union), [(3.0, true), (8.0, false)]);
+
+        let lens = Csg::intersection(sphere(0.0, 2.0), sphere(2.0, 1.0));
+        assert_eq!(boundaries(&lens), [(6.0, true), (7.0, false)]);
+
+        let bitten = Csg::difference(sphere(0.0, 2.0), sphere(2.0, 1.0));
+        assert_eq!(boundaries(&bitten), [(3.0, true), (6.0, false)]);
+    }
+
+    #[test]
+    fn csg_difference_with_a_cuboid() {
+        // A box over [1, 3] along the X axis cuts the end off a sphere over [-2, 2].
+        let cuboid = HittableObjects::Cuboid(Cuboid::new(
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(3.0, 1.0, 1.0),
+            HitRecord::default().material,
+        ));
+        let cut = Csg::difference(sphere(0.0, 2.0), cuboid.clone());
+        assert_eq!(boundaries(&cut), [(3.0, true), (6.0, false)]);
+
+        let capped = Csg::intersection(sphere(0.0, 2.0), cuboid);
+        assert_eq!(boundaries(&capped), [(6.0, true), (7.0, false)]);
+    }
+
+    #[test]
+    fn csg_difference_flips_the_normals_of_the_removed_shape() {
+        // A sphere with a spherical cavity: the ray hits the inner wall from inside the cavity.
+        let hollow = Csg::difference(sphere(0.0, 2.0), sphere(0.0, 1.0));
+        let ray = Ray::new(Point3::zeroes(), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = hollow.hit(&ray, 0.001, f64::INFINITY).unwrap();
+
+        assert!((hit.t - 1.0).abs() < 1e-9);
+        assert!(hit.front_face);
+        // The wall of the cavity faces its center, against the ray.
+        assert!((hit.normal.x + 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,148 @@
+//! This module defines the Cuboid (box) type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::xy_rect::XyRect;
+use crate::shapes::xz_rect::XzRect;
+use crate::shapes::yz_rect::YzRect;
+use crate::shapes::HittableObjects;
+use crate::vector::Point3;
+
+/// An axis-aligned box made of six rectangles that share the same material.
+#[derive(Debug, Clone)]
+pub struct Cuboid {
+    /// Minimum corner of the box.
+    box_min: Point3,
+    /// Maximum corner of the box.
+    box_max: Point3,
+    /// The six sides of the box.
+    sides: HittableList,
+}
+
+impl Cuboid {
+    /// Creates and returns an owned box spanning two opposite corners.
+    pub fn new(p0: Point3, p1: Point3, material: Materials) -> Self {
+        let box_min = Point3::new(p0.x.min(p1.x), p0.y.min(p1.y), p0.z.min(p1.z));
+        let box_max = Point3::new(p0.x.max(p1.x), p0.y.max(p1.y), p0.z.max(p1.z));
+        let (min, max) = (box_min, box_max);
+
+        // The sides at the minimum corner face the negative axes, so every normal points out.
+        let mut sides = HittableList::default();
+        sides.add(HittableObjects::XyRect(XyRect::new(
+            min.x,
+            max.x,
+            min.y,
+            max.y,
+            max.z,
+            material.clone(),
+        )));
+        sides.add(HittableObjects::XyRect(
+            XyRect::new(min.x, max.x, min.y, max.y, min.z, material.clone()).flipped(),
+        ));
+        sides.add(HittableObjects::XzRect(XzRect::new(
+            min.x,
+            max.x,
+            min.z,
+            max.z,
+            max.y,
+            material.clone(),
+        )));
+        sides.add(HittableObjects::XzRect(
+            XzRect::new(min.x, max.x, min.z, max.z, min.y, material.clone()).flipped(),
+        ));
+        sides.add(HittableObjects::YzRect(YzRect::new(
+            min.y,
+            max.y,
+            min.z,
+            max.z,
+            max.x,
+            material.clone(),
+        )));
+        sides.add(HittableObjects::YzRect(
+            YzRect::new(min.y, max.y, min.z, max.z, min.x, material).flipped(),
+        ));
+
+        Self {
+            box_min,
+            box_max,
+            sides,
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        Some(AaBb::new(self.box_min, self.box_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cuboid;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn cuboid_faces_point_outwards() {
+        let cuboid = Cuboid::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(-1.0, -2.0, -3.0),
+            HitRecord::default().material,
+        );
+        let bbox = cuboid.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.min(), Point3::new(-1.0, -2.0, -3.0));
+        assert_eq!(bbox.max(), Point3::new(1.0, 2.0, 3.0));
+
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes {
+            for outward in [axis, -axis] {
+                // From outside, every face is a front face whose normal points along the ray's
+                // reverse direction; from the center, the same face is a back face.
+                let ray = Ray::new(outward * 10.0, -outward, 0.0);
+                let hit = cuboid.hit(&ray, 0.001, f64::INFINITY).unwrap();
+                assert!(hit.front_face);
+                assert_eq!(hit.normal, outward);
+
+                let ray = Ray::new(Point3::zeroes(), outward, 0.0);
+                let hit = cuboid.hit(&ray, 0.001, f64::INFINITY).unwrap();
+                assert!(!hit.front_face);
+                assert_eq!(hit.normal, -outward);
+            }
+        }
+    }
+
+    #[test]
+    fn cuboid_hits_the_nearest_face() {
+        let cuboid = Cuboid::new(
+            Point3::zeroes(),
+            Point3::new(1.0, 2.0, 3.0),
+            HitRecord::default().material,
+        );
+        // Diagonally through the box, entering through the min X face.
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.5, 0.5), 0.0);
+        let hit = cuboid.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-9);
+        assert_eq!(hit.p, Point3::new(0.0, 1.0, 1.0));
+
+        // The far side is hit once the near one is out of range.
+        let hit = cuboid.hit(&ray, 1.5, f64::INFINITY).unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-9);
+
+        let ray = Ray::new(Point3::new(-1.0, 2.5, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(cuboid.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
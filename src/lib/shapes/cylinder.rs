@@ -0,0 +1,137 @@
+//! This module defines the Cylinder type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::frame::{angle_u, Frame};
+use crate::utilities::solve_quadratic;
+use crate::vector::{Point3, Vec3};
+
+/// A circular cylinder between two points, either closed by caps or open at both ends.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    /// Frame centered on the base, with the axis of the cylinder as its Y axis.
+    frame: Frame,
+    /// Radius of the cylinder.
+    radius: f64,
+    /// Distance between the base and the top.
+    height: f64,
+    /// Whether the ends are closed by disks.
+    capped: bool,
+    /// Material for the cylinder.
+    material: Materials,
+}
+
+impl Cylinder {
+    /// Creates and returns an owned cylinder from the centers of its base and top. On the side
+    /// the 'u' texture coordinate goes around the axis and 'v' grows from the base to the top;
+    /// on the caps 'v' grows from the center to the rim.
+    pub fn new(base: Point3, top: Point3, radius: f64, capped: bool, material: Materials) -> Self {
+        let axis = top - base;
+        Self {
+            frame: Frame::new(base, axis),
+            radius,
+            height: axis.length(),
+            capped,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (o, d) = self.frame.local_ray(ray);
+        // Closest hit so far as (t, local normal, u, v).
+        let mut closest: Option<(f64, Vec3, f64, f64)> = None;
+        let mut t_max = t_max;
+
+        let a = d.x * d.x + d.z * d.z;
+        let b = 2.0 * (o.x * d.x + o.z * d.z);
+        let c = o.x * o.x + o.z * o.z - self.radius * self.radius;
+        for t in solve_quadratic(a, b, c) {
+            let y = o.y + t * d.y;
+            if t >= t_min && t <= t_max && (0.0..=self.height).contains(&y) {
+                let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+                let normal = Vec3::new(x, 0.0, z) / self.radius;
+                closest = Some((t, normal, angle_u(x, z), y / self.height));
+                t_max = t;
+                break;
+            }
+        }
+
+        if self.capped {
+            for (cap_y, normal_y) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (cap_y - o.y) / d.y;
+                if t.is_nan() || t < t_min || t > t_max {
+                    continue;
+                }
+                let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+                let distance = (x * x + z * z).sqrt();
+                if distance <= self.radius {
+                    let normal = Vec3::new(0.0, normal_y, 0.0);
+                    closest = Some((t, normal, angle_u(x, z), distance / self.radius));
+                    t_max = t;
+                }
+            }
+        }
+
+        let (t, normal, u, v) = closest?;
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            u,
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, self.frame.world_vector(normal));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        let r = self.radius;
+        Some(
+            self.frame
+                .bounding_box(Point3::new(-r, 0.0, -r), Point3::new(r, self.height, r)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cylinder;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn cylinder_side_and_caps() {
+        // A cylinder along the X axis, from x = 0 to x = 2.
+        let base = Point3::zeroes();
+        let top = Point3::new(2.0, 0.0, 0.0);
+        let material = HitRecord::default().material;
+        let capped = Cylinder::new(base, top, 1.0, true, material.clone());
+        let open = Cylinder::new(base, top, 1.0, false, material);
+
+        let side = Ray::new(Point3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = capped.hit(&side, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!((hit.normal.y - 1.0).abs() < 1e-9);
+        assert!((hit.v - 0.25).abs() < 1e-9);
+
+        let along = Ray::new(Point3::new(-3.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = capped.hit(&along, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-9);
+        assert!((hit.normal.x + 1.0).abs() < 1e-9);
+        assert!(open.hit(&along, 0.001, f64::INFINITY).is_none());
+
+        let bbox = capped.bounding_box(0.0, 1.0).unwrap();
+        assert!((bbox.max().x - 2.0).abs() < 1e-3 && (bbox.min().y + 1.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,104 @@
+//! This module defines the Disk type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::frame::{angle_u, Frame};
+use crate::vector::{Point3, Vec3};
+
+/// A flat disk with center, normal, radius and material.
+#[derive(Debug, Clone)]
+pub struct Disk {
+    /// Frame centered on the disk, with the normal as its Y axis.
+    frame: Frame,
+    /// Radius of the disk.
+    radius: f64,
+    /// Material for the disk.
+    material: Materials,
+}
+
+impl Disk {
+    /// Creates and returns an owned disk. The 'u' texture coordinate goes around the center and
+    /// 'v' grows from the center to the rim.
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Materials) -> Self {
+        Self {
+            frame: Frame::new(center, normal),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (origin, direction) = self.frame.local_ray(ray);
+        // Rays parallel to the disk give an infinite or undefined 't' and never hit it.
+        let t = -origin.y / direction.y;
+        if !t.is_finite() || t < t_min || t > t_max {
+            return None;
+        }
+
+        let x = origin.x + t * direction.x;
+        let z = origin.z + t * direction.z;
+        let distance = (x * x + z * z).sqrt();
+        if distance > self.radius {
+            return None;
+        }
+
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            angle_u(x, z),
+            distance / self.radius,
+            false,
+        );
+        hit_record.set_face_normal(ray, self.frame.world_vector(Vec3::new(0.0, 1.0, 0.0)));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        let r = self.radius;
+        Some(
+            self.frame
+                .bounding_box(Point3::new(-r, 0.0, -r), Point3::new(r, 0.0, r)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Disk;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn disk_hits_inside_the_radius_and_ignores_parallel_rays() {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let disk = Disk::new(Point3::zeroes(), up, 2.0, HitRecord::default().material);
+
+        let ray = Ray::new(Point3::new(1.0, 3.0, 0.0), -up, 0.0);
+        let hit = disk.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-9);
+        assert!(hit.front_face);
+        assert!((hit.v - 0.5).abs() < 1e-9);
+        let outside = Ray::new(Point3::new(2.5, 3.0, 0.0), -up, 0.0);
+        assert!(disk.hit(&outside, 0.001, f64::INFINITY).is_none());
+
+        // Rays in the plane of the disk, or parallel to it on either side, miss.
+        for y in [-1.0, 0.0, 1.0] {
+            let parallel = Ray::new(Point3::new(0.0, y, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+            assert!(disk.hit(&parallel, 0.001, f64::INFINITY).is_none());
+        }
+
+        let bbox = disk.bounding_box(0.0, 1.0).unwrap();
+        assert!((bbox.max().x - 2.0).abs() < 1e-3 && bbox.max().y - bbox.min().y < 1e-2);
+    }
+}
@@ -0,0 +1,80 @@
+//! This module defines the local Frame used by the analytic shapes that are symmetric around an
+//! axis, such as disks, cylinders, cones and tori.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::ray::Ray;
+use crate::utilities::PI;
+use crate::vector::{Point3, Vec3};
+
+/// An orthonormal frame whose local Y axis is the axis of a shape.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Frame {
+    /// Origin of the frame in world space.
+    origin: Point3,
+    /// Local X axis in world space.
+    x: Vec3,
+    /// Local Y axis (the axis of the shape) in world space.
+    y: Vec3,
+    /// Local Z axis in world space.
+    z: Vec3,
+}
+
+impl Frame {
+    /// Creates a frame at the origin whose Y axis points along the given axis.
+    pub(crate) fn new(origin: Point3, axis: Vec3) -> Self {
+        let y = axis.to_unit();
+        // Any vector that is not parallel to the axis completes the basis.
+        let helper = if y.x.abs() > 0.9 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let z = Vec3::cross(helper, y).to_unit();
+        let x = Vec3::cross(y, z);
+        Self { origin, x, y, z }
+    }
+
+    /// Returns the origin and the direction of a ray in local coordinates. Distances along the
+    /// ray are the same in both spaces.
+    pub(crate) fn local_ray(&self, ray: &Ray) -> (Point3, Vec3) {
+        let local = |v: Vec3| {
+            Vec3::new(
+                Vec3::dot(v, self.x),
+                Vec3::dot(v, self.y),
+                Vec3::dot(v, self.z),
+            )
+        };
+        (local(ray.origin() - self.origin), local(ray.direction()))
+    }
+
+    /// Returns a local vector, such as a normal, in world space.
+    pub(crate) fn world_vector(&self, v: Vec3) -> Vec3 {
+        v.x * self.x + v.y * self.y + v.z * self.z
+    }
+
+    /// Returns the bounding box in world space of a box in local coordinates.
+    pub(crate) fn bounding_box(&self, min: Point3, max: Point3) -> AaBb {
+        let mut small = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut big = -small;
+        for corner in 0..8 {
+            let local = Vec3::new(
+                if corner & 1 == 0 { min.x } else { max.x },
+                if corner & 2 == 0 { min.y } else { max.y },
+                if corner & 4 == 0 { min.z } else { max.z },
+            );
+            let p = self.origin + self.world_vector(local);
+            small = Point3::new(small.x.min(p.x), small.y.min(p.y), small.z.min(p.z));
+            big = Point3::new(big.x.max(p.x), big.y.max(p.y), big.z.max(p.z));
+        }
+        let padding = Vec3::new(BOX_PADDING, BOX_PADDING, BOX_PADDING);
+        AaBb::new(small - padding, big + padding)
+    }
+}
+
+/// Returns the angle around the local Y axis of a point as a texture coordinate in [0, 1).
+pub(crate) fn angle_u(x: f64, z: f64) -> f64 {
+    (z.atan2(x) + PI) / (2.0 * PI)
+}
@@ -0,0 +1,417 @@
+//! This module defines the Heightfield (terrain) type, a regular grid of heights intersected by
+//! walking the cells under a Ray, and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::loaders::data_error;
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::triangle::{intersect_triangle, triangle_hit_record};
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::perlin::Perlin;
+use crate::utilities::INFINITY;
+use crate::vector::{Point3, Vec3};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A terrain over a rectangle of the XZ plane, with one height per sample of a regular grid.
+/// Each cell between four samples is split into two triangles with interpolated normals.
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    /// Number of samples along the X axis.
+    columns: usize,
+    /// Number of samples along the Z axis.
+    rows: usize,
+    /// Heights above the corner in rows along the X axis, shared between clones.
+    heights: Arc<Vec<f64>>,
+    /// Normals at the samples, shared between clones.
+    normals: Arc<Vec<Vec3>>,
+    /// Corner of the terrain with the smallest X and Z, at height zero.
+    corner: Point3,
+    /// Size of a cell along the X and Z axes.
+    cell_size: (f64, f64),
+    /// Box that contains the whole terrain.
+    bounds: AaBb,
+    /// Material for the terrain.
+    material: Materials,
+}
+
+impl Heightfield {
+    /// Creates and returns an owned terrain from a grid of heights in rows along the X axis.
+    /// The grid covers 'size.x' by 'size.z' from the corner, and the heights are multiplied by
+    /// 'size.y'. The 'u' and 'v' texture coordinates go from 0 to 1 along X and Z.
+    pub fn new(
+        heights: Vec<f64>,
+        columns: usize,
+        rows: usize,
+        corner: Point3,
+        size: Vec3,
+        material: Materials,
+    ) -> Self {
+        assert!(columns >= 2 && rows >= 2, "a heightfield needs 2x2 samples");
+        assert_eq!(heights.len(), columns * rows, "wrong number of heights");
+
+        let heights: Vec<f64> = heights.iter().map(|h| h * size.y).collect();
+        let cell_size = (size.x / (columns - 1) as f64, size.z / (rows - 1) as f64);
+
+        // Normals from the slopes between the neighbours of each sample.
+        let height = |i: usize, j: usize| heights[j * columns + i];
+        let mut normals = Vec::with_capacity(heights.len());
+        for j in 0..rows {
+            for i in 0..columns {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(columns - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(rows - 1));
+                let slope_x = (height(i1, j) - height(i0, j)) / ((i1 - i0) as f64 * cell_size.0);
+                let slope_z = (height(i, j1) - height(i, j0)) / ((j1 - j0) as f64 * cell_size.1);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).to_unit());
+            }
+        }
+
+        let lowest = heights.iter().cloned().fold(INFINITY, f64::min);
+        let highest = heights.iter().cloned().fold(-INFINITY, f64::max);
+        let bounds = AaBb::new(
+            Point3::new(corner.x, corner.y + lowest - BOX_PADDING, corner.z),
+            Point3::new(
+                corner.x + size.x,
+                corner.y + highest + BOX_PADDING,
+                corner.z + size.z,
+            ),
+        );
+
+        Self {
+            columns,
+            rows,
+            heights: Arc::new(heights),
+            normals: Arc::new(normals),
+            corner,
+            cell_size,
+            bounds,
+            material,
+        }
+    }
+
+    /// Creates a terrain from the brightness of the pixels of an image, from 0 for black to 1
+    /// for white. The top row of the image is at the corner and its columns go along X.
+    /// Returns an InvalidData error when the image is narrower or shorter than 2 pixels.
+    pub fn from_image(
+        image: &ImageTexture,
+        corner: Point3,
+        size: Vec3,
+        material: Materials,
+    ) -> Result<Self, Error> {
+        let (columns, rows) = (image.width(), image.height());
+        if columns < 2 || rows < 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "a heightfield needs 2x2 samples, the image has {}x{} pixels",
+                    columns, rows
+                ),
+            ));
+        }
+        let mut heights = Vec::with_capacity(columns * rows);
+        for y in 0..rows {
+            for x in 0..columns {
+                let color = image.pixel(x, y);
+                heights.push(0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b);
+            }
+        }
+        Ok(Self::new(heights, columns, rows, corner, size, material))
+    }
+
+    /// Loads a grayscale PNG or PGM image and returns a terrain from its brightness.
+    pub fn load(
+        path: &Path,
+        corner: Point3,
+        size: Vec3,
+        material: Materials,
+    ) -> Result<Self, Error> {
+        let image = ImageTexture::load(path)?;
+        Self::from_image(&image, corner, size, material)
+            .map_err(|err| data_error(&path.display().to_string(), &err.to_string()))
+    }
+
+    /// Creates a terrain from a sum of octaves of Perlin noise, with heights between 0 and 1.
+    /// The frequency is the number of noise features across the terrain.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_noise(
+        perlin: &Perlin,
+        columns: usize,
+        rows: usize,
+        frequency: f64,
+        octaves: usize,
+        corner: Point3,
+        size: Vec3,
+        material: Materials,
+    ) -> Self {
+        let mut heights = Vec::with_capacity(columns * rows);
+        for j in 0..rows {
+            for i in 0..columns {
+                // The noise is sampled away from its lattice planes, where it is always zero.
+                let p = Point3::new(
+                    frequency * i as f64 / (columns - 1) as f64 + 0.5,
+                    0.5,
+                    frequency * j as f64 / (rows - 1) as f64 + 0.5,
+                );
+                let mut sum = 0.0;
+                let mut weight = 0.5;
+                let mut scale = 1.0;
+                for _ in 0..octaves {
+                    sum += weight * perlin.noise(p * scale);
+                    weight *= 0.5;
+                    scale *= 2.0;
+                }
+                heights.push((0.5 + sum).clamp(0.0, 1.0));
+            }
+        }
+        Self::new(heights, columns, rows, corner, size, material)
+    }
+
+    /// Returns the position of the sample in column i and row j.
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        Point3::new(
+            self.corner.x + i as f64 * self.cell_size.0,
+            self.corner.y + self.heights[j * self.columns + i],
+            self.corner.z + j as f64 * self.cell_size.1,
+        )
+    }
+
+    /// Intersects a Ray with the two triangles of the cell in column i and row j, and returns
+    /// the nearest hit.
+    fn hit_cell(&self, ray: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let samples = [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)];
+        let [p00, p10, p01, p11] = samples.map(|(i, j)| self.vertex(i, j));
+        let [n00, n10, n01, n11] = samples.map(|(i, j)| self.normals[j * self.columns + i]);
+        let uv = |(i, j): (usize, usize)| {
+            (
+                i as f64 / (self.columns - 1) as f64,
+                j as f64 / (self.rows - 1) as f64,
+            )
+        };
+        let [uv00, uv10, uv01, uv11] = samples.map(uv);
+
+        // Both triangles share the diagonal from the first to the last sample, and are wound
+        // so that their normals point up.
+        let triangles = [
+            ([p00, p11, p10], [n00, n11, n10], [uv00, uv11, uv10]),
+            ([p00, p01, p11], [n00, n01, n11], [uv00, uv01, uv11]),
+        ];
+        let mut closest_so_far = t_max;
+        let mut nearest = None;
+        for (vertices, normals, uvs) in triangles {
+            if let Some((t, b1, b2)) = intersect_triangle(ray, &vertices, t_min, closest_so_far) {
+                closest_so_far = t;
+                nearest = Some((t, (b1, b2), vertices, normals, uvs));
+            }
+        }
+        let (t, barycentric, [v0, v1, v2], normals, uvs) = nearest?;
+        Some(triangle_hit_record(
+            ray,
+            t,
+            barycentric,
+            Vec3::cross(v1 - v0, v2 - v0),
+            Some(normals),
+            uvs,
+            self.material.clone(),
+        ))
+    }
+
+    /// Returns the lowest and highest heights of the cell in column i and row j.
+    fn cell_range(&self, i: usize, j: usize) -> (f64, f64) {
+        let h = |i: usize, j: usize| self.corner.y + self.heights[j * self.columns + i];
+        let corners = [h(i, j), h(i + 1, j), h(i, j + 1), h(i + 1, j + 1)];
+        let lowest = corners.iter().cloned().fold(INFINITY, f64::min);
+        let highest = corners.iter().cloned().fold(-INFINITY, f64::max);
+        (lowest, highest)
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.hit_interval(ray, t_min, t_max)?;
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let cells = (self.columns - 1, self.rows - 1);
+        let start = ray.at(t_enter);
+
+        // Walk the cells under the ray in order with a 2D digital differential analyzer, from
+        // the cell where the ray enters the bounds.
+        let first_cell = |offset: f64, size: f64, count: usize| {
+            ((offset / size).floor().max(0.0) as usize).min(count - 1)
+        };
+        let mut i = first_cell(start.x - self.corner.x, self.cell_size.0, cells.0);
+        let mut j = first_cell(start.z - self.corner.z, self.cell_size.1, cells.1);
+
+        // Distance along the ray to the next cell boundary and between boundaries, per axis.
+        let axis = |cell: usize, origin: f64, corner: f64, direction: f64, size: f64| {
+            if direction > 0.0 {
+                let boundary = corner + (cell + 1) as f64 * size;
+                ((boundary - origin) / direction, size / direction)
+            } else if direction < 0.0 {
+                let boundary = corner + cell as f64 * size;
+                ((boundary - origin) / direction, -size / direction)
+            } else {
+                (INFINITY, INFINITY)
+            }
+        };
+        let (mut next_x, delta_x) = axis(i, origin.x, self.corner.x, direction.x, self.cell_size.0);
+        let (mut next_z, delta_z) = axis(j, origin.z, self.corner.z, direction.z, self.cell_size.1);
+
+        let mut cell_enter = t_enter;
+        loop {
+            let cell_exit = next_x.min(next_z).min(t_exit);
+
+            // Skip the triangles when the ray passes above or below the whole cell.
+            let (lowest, highest) = self.cell_range(i, j);
+            let (y0, y1) = (ray.at(cell_enter).y, ray.at(cell_exit).y);
+            if y0.min(y1) <= highest && y0.max(y1) >= lowest {
+                if let Some(hit) = self.hit_cell(ray, i, j, t_min, t_max) {
+                    return Some(hit);
+                }
+            }
+
+            if cell_exit >= t_exit {
+                return None;
+            }
+            cell_enter = cell_exit;
+            if next_x < next_z {
+                if direction.x > 0.0 {
+                    i += 1;
+                } else if i > 0 {
+                    i -= 1;
+                } else {
+                    return None;
+                }
+                if i >= cells.0 {
+                    return None;
+                }
+                next_x += delta_x;
+            } else {
+                if direction.z > 0.0 {
+                    j += 1;
+                } else if j > 0 {
+                    j -= 1;
+                } else {
+                    return None;
+                }
+                if j >= cells.1 {
+                    return None;
+                }
+                next_z += delta_z;
+            }
+        }
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heightfield;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::textures::image_texture::ImageTexture;
+    use crate::textures::perlin::Perlin;
+    use crate::utilities::random_float_range;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn heightfield_ramp_hit_uv_and_normal() {
+        // Heights rise along X from 0 to 2 over a 2x2 square.
+        let heights = vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0];
+        let ramp = Heightfield::new(
+            heights,
+            3,
+            2,
+            Point3::zeroes(),
+            Vec3::new(2.0, 2.0, 2.0),
+            HitRecord::default().material,
+        );
+        let ray = Ray::new(Point3::new(0.5, 5.0, 1.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = ramp.hit(&ray, 0.001, f64::INFINITY).unwrap();
+
+        assert!((hit.t - 4.5).abs() < 1e-9);
+        assert!((hit.u - 0.25).abs() < 1e-9 && (hit.v - 0.75).abs() < 1e-9);
+        assert!(hit.front_face);
+        let expected = Vec3::new(-1.0, 1.0, 0.0).to_unit();
+        assert!((hit.normal - expected).length() < 1e-9);
+    }
+
+    #[test]
+    fn heightfield_walk_matches_every_cell() {
+        let terrain = Heightfield::from_noise(
+            &Perlin::new(),
+            17,
+            9,
+            4.0,
+            3,
+            Point3::new(-4.0, 0.0, -2.0),
+            Vec3::new(8.0, 2.0, 4.0),
+            HitRecord::default().material,
+        );
+        for _ in 0..500 {
+            let origin = Point3::new(
+                random_float_range(-6.0, 6.0),
+                random_float_range(0.5, 4.0),
+                random_float_range(-4.0, 4.0),
+            );
+            let target = Point3::new(
+                random_float_range(-4.0, 4.0),
+                random_float_range(0.0, 2.0),
+                random_float_range(-2.0, 2.0),
+            );
+            let ray = Ray::new(origin, target - origin, 0.0);
+
+            let mut brute_force: Option<f64> = None;
+            for j in 0..8 {
+                for i in 0..16 {
+                    let t_max = brute_force.unwrap_or(f64::INFINITY);
+                    if let Some(hit) = terrain.hit_cell(&ray, i, j, 0.001, t_max) {
+                        brute_force = Some(hit.t);
+                    }
+                }
+            }
+            let walked = terrain.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.t);
+            match (walked, brute_force) {
+                (Some(a), Some(b)) => assert!((a - b).abs() < 1e-9, "{} != {}", a, b),
+                (a, b) => assert_eq!(a.is_some(), b.is_some()),
+            }
+        }
+    }
+
+    #[test]
+    fn heightfield_rejects_images_without_cells() {
+        for data in [&b"P2 1 3 255 0 0 0"[..], &b"P2 3 1 255 0 0 0"[..]] {
+            let image = ImageTexture::decode(data, "line.pgm").unwrap();
+            let err = Heightfield::from_image(
+                &image,
+                Point3::zeroes(),
+                Vec3::new(1.0, 1.0, 1.0),
+                HitRecord::default().material,
+            )
+            .err()
+            .unwrap();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+
+        let path = std::env::temp_dir().join(format!("line_{}.pgm", std::process::id()));
+        std::fs::write(&path, b"P2 1 3 255 0 0 0").unwrap();
+        let err = Heightfield::load(
+            &path,
+            Point3::zeroes(),
+            Vec3::new(1.0, 1.0, 1.0),
+            HitRecord::default().material,
+        )
+        .err()
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("2x2 samples"));
+    }
+}
@@ -0,0 +1,174 @@
+//! This module defines the two-level acceleration structure: Instances place a shared
+//! bottom-level hierarchy in the Scene with a Transform, and a TopLevel hierarchy is built over
+//! the boxes of the Instances.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::linear_bvh::LinearBvh;
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::transform::Transform;
+use std::sync::Arc;
+
+/// A placement of a shared bottom-level hierarchy, such as the one of a model, in the Scene.
+/// Instances of the same hierarchy share its nodes and shapes.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    /// The shared bottom-level hierarchy, in object space.
+    bottom_level: Arc<LinearBvh>,
+    /// Transform that maps the hierarchy from object space to world space.
+    transform: Arc<Transform>,
+    /// Box of the hierarchy in world space.
+    bbox: Option<AaBb>,
+}
+
+impl Instance {
+    /// Creates and returns an owned instance of a shared hierarchy with the Transform applied.
+    pub fn new(bottom_level: &Arc<LinearBvh>, transform: Transform) -> Self {
+        let bbox = bottom_level
+            .bounding_box(0.0, 0.0)
+            .map(|object_box| transform.apply_box(object_box));
+        Self {
+            bottom_level: Arc::clone(bottom_level),
+            transform: Arc::new(transform),
+            bbox,
+        }
+    }
+
+    /// Returns the shared bottom-level hierarchy.
+    pub fn bottom_level(&self) -> &Arc<LinearBvh> {
+        &self.bottom_level
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.transform
+            .hit_through(self.bottom_level.as_ref(), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        self.bbox
+    }
+}
+
+/// Hierarchy over the world-space boxes of Instances, whose leaves descend into the shared
+/// bottom-level hierarchies.
+#[derive(Debug, Clone)]
+pub struct TopLevel {
+    /// Hierarchy over the Instances.
+    bvh: LinearBvh,
+}
+
+impl TopLevel {
+    /// Creates and returns an owned hierarchy over the Instances. Panics if an Instance has an
+    /// empty bottom-level hierarchy.
+    pub fn new(instances: Vec<Instance>) -> Self {
+        let mut list = HittableList::default();
+        for instance in instances {
+            list.add(HittableObjects::Instance(instance));
+        }
+        Self {
+            bvh: LinearBvh::new(&list, 0.0, 0.0),
+        }
+    }
+
+    /// Returns the number of Instances.
+    pub fn total_instances(&self) -> usize {
+        self.bvh.total_shapes()
+    }
+}
+
+impl Hittable for TopLevel {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        self.bvh.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Instance, TopLevel};
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::linear_bvh::LinearBvh;
+    use crate::ray::Ray;
+    use crate::shapes::cone::Cone;
+    use crate::shapes::cylinder::Cylinder;
+    use crate::shapes::HittableObjects;
+    use crate::transform::Transform;
+    use crate::vector::{Point3, Vec3};
+    use std::sync::Arc;
+
+    #[test]
+    fn forest_of_instances_shares_one_tree() {
+        // A tree: a trunk of height 1 under a cone of height 2.
+        let material = HitRecord::default().material;
+        let mut tree = HittableList::default();
+        tree.add(HittableObjects::Cylinder(Cylinder::new(
+            Point3::zeroes(),
+            Point3::new(0.0, 1.0, 0.0),
+            0.1,
+            true,
+            material.clone(),
+        )));
+        tree.add(HittableObjects::Cone(Cone::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+            0.5,
+            true,
+            material,
+        )));
+        let tree = Arc::new(LinearBvh::new(&tree, 0.0, 1.0));
+
+        // A 100 x 100 grid of trees, two units apart and scaled up with the distance along X.
+        let mut instances = Vec::new();
+        for i in 0..100 {
+            for j in 0..100 {
+                let scale = 1.0 + i as f64 / 100.0;
+                let transform = Transform::scale(Vec3::new(scale, scale, scale))
+                    .then(&Transform::rotate(Vec3::new(0.0, 1.0, 0.0), 7.0 * j as f64))
+                    .then(&Transform::translate(Vec3::new(
+                        2.0 * i as f64,
+                        0.0,
+                        2.0 * j as f64,
+                    )));
+                instances.push(Instance::new(&tree, transform));
+            }
+        }
+        assert!(instances
+            .iter()
+            .all(|instance| Arc::ptr_eq(instance.bottom_level(), &tree)));
+        let forest = TopLevel::new(instances);
+        assert_eq!(forest.total_instances(), 10_000);
+        assert_eq!(Arc::strong_count(&tree), 10_001);
+
+        // The boxes of the trees are transformed, so the tallest ones at the far side of the grid
+        // give the top of the box.
+        let bbox = forest.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.max().y >= 3.0 * 1.99 && bbox.max().y < 3.0 * 1.99 + 0.01);
+        assert!(bbox.max().x > 198.0 + 0.99 && bbox.max().x < 200.0);
+
+        // Straight down onto the cone of the tree at i = 50, scaled 1.5 times, a third of the
+        // way from its axis to its base radius of 0.75, which is a unit below its apex at 4.5.
+        let ray = Ray::new(
+            Point3::new(100.25, 10.0, 20.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            0.0,
+        );
+        let hit = forest.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 6.5).abs() < 1e-6);
+        assert!(hit.normal.x > 0.0 && hit.normal.y > 0.0);
+        let ray = Ray::new(
+            Point3::new(101.0, 10.0, 21.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            0.0,
+        );
+        assert!(forest.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+//! This module defines the infinite Plane type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// Rays closer than this to parallel with the plane are considered to miss it.
+const PARALLEL_EPSILON: f64 = 1e-9;
+
+/// An infinite plane through a point, facing the direction of its normal.
+#[derive(Debug, Clone)]
+pub struct Plane {
+    /// A point on the plane, which is also the origin of its texture coordinates.
+    point: Point3,
+    /// Unit normal of the plane.
+    normal: Vec3,
+    /// Unit direction of the 'u' texture coordinate along the plane.
+    u_axis: Vec3,
+    /// Unit direction of the 'v' texture coordinate along the plane.
+    v_axis: Vec3,
+    /// Material for the plane.
+    material: Materials,
+}
+
+impl Plane {
+    /// Creates and returns an owned plane. The texture coordinates repeat every unit along two
+    /// perpendicular directions of the plane.
+    pub fn new(point: Point3, normal: Vec3, material: Materials) -> Self {
+        let normal = normal.to_unit();
+        // Any vector that is not parallel to the normal gives a basis of the plane.
+        let helper = if normal.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let u_axis = Vec3::cross(helper, normal).to_unit();
+        let v_axis = Vec3::cross(normal, u_axis);
+        Self {
+            point,
+            normal,
+            u_axis,
+            v_axis,
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denominator = Vec3::dot(self.normal, ray.direction());
+        if denominator.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let t = Vec3::dot(self.point - ray.origin(), self.normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.point;
+        let u = Vec3::dot(offset, self.u_axis).rem_euclid(1.0);
+        let v = Vec3::dot(offset, self.v_axis).rem_euclid(1.0);
+        let mut hit_record =
+            HitRecord::new(p, Vec3::default(), self.material.clone(), t, u, v, false);
+        hit_record.set_face_normal(ray, self.normal);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        // An infinite plane cannot be bounded.
+        None
+    }
+}
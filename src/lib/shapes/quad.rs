@@ -0,0 +1,154 @@
+//! This module defines the Quad (parallelogram) type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// Rays closer than this to parallel with the quad are considered to miss it.
+const PARALLEL_EPSILON: f64 = 1e-9;
+
+/// A parallelogram spanned by two edges from a corner, in any orientation.
+#[derive(Debug, Clone)]
+pub struct Quad {
+    /// Corner of the quad.
+    corner: Point3,
+    /// First edge from the corner, along which the 'u' texture coordinate grows.
+    u: Vec3,
+    /// Second edge from the corner, along which the 'v' texture coordinate grows.
+    v: Vec3,
+    /// Unit normal, following the right-hand rule from 'u' to 'v'.
+    normal: Vec3,
+    /// Normal divided by its squared length, used to find the coordinates of hit points.
+    w: Vec3,
+    /// Material for the quad.
+    material: Materials,
+}
+
+impl Quad {
+    /// Creates and returns an owned quad from a corner and its two edges.
+    pub fn new(corner: Point3, u: Vec3, v: Vec3, material: Materials) -> Self {
+        let n = Vec3::cross(u, v);
+        Self {
+            corner,
+            u,
+            v,
+            normal: n.to_unit(),
+            w: n / n.length_squared(),
+            material,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denominator = Vec3::dot(self.normal, ray.direction());
+        if denominator.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let t = Vec3::dot(self.corner - ray.origin(), self.normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        // Coordinates of the hit point in the frame of the edges.
+        let p = ray.at(t);
+        let planar = p - self.corner;
+        let alpha = Vec3::dot(self.w, Vec3::cross(planar, self.v));
+        let beta = Vec3::dot(self.w, Vec3::cross(self.u, planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut hit_record = HitRecord::new(
+            p,
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            alpha,
+            beta,
+            false,
+        );
+        hit_record.set_face_normal(ray, self.normal);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        let corners = [
+            self.corner,
+            self.corner + self.u,
+            self.corner + self.v,
+            self.corner + self.u + self.v,
+        ];
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for corner in &corners[1..] {
+            min = Point3::new(
+                min.x.min(corner.x),
+                min.y.min(corner.y),
+                min.z.min(corner.z),
+            );
+            max = Point3::new(
+                max.x.max(corner.x),
+                max.y.max(corner.y),
+                max.z.max(corner.z),
+            );
+        }
+        // The bounding box must have non-zero width in each dimension, so pad the thin ones.
+        let pad = |min: f64, max: f64| {
+            if max - min < BOX_PADDING {
+                (min - BOX_PADDING, max + BOX_PADDING)
+            } else {
+                (min, max)
+            }
+        };
+        let (x0, x1) = pad(min.x, max.x);
+        let (y0, y1) = pad(min.y, max.y);
+        let (z0, z1) = pad(min.z, max.z);
+        Some(AaBb::new(Point3::new(x0, y0, z0), Point3::new(x1, y1, z1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quad;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn quad_tilted_hit_and_box() {
+        // A unit square in the XY plane, tilted 45 degrees around the X axis.
+        let (u, v) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 1.0).to_unit());
+        let quad = Quad::new(Point3::zeroes(), u, v, HitRecord::default().material);
+        let center = 0.5 * u + 0.5 * v;
+        let ray = Ray::new(
+            center + Vec3::new(0.0, 0.0, -3.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            0.0,
+        );
+        let hit = quad.hit(&ray, 0.001, f64::INFINITY).unwrap();
+
+        assert!((hit.u - 0.5).abs() < 1e-9 && (hit.v - 0.5).abs() < 1e-9);
+        assert!((hit.normal.y - 0.5f64.sqrt()).abs() < 1e-9);
+        assert!((hit.normal.z + 0.5f64.sqrt()).abs() < 1e-9);
+
+        let ray = Ray::new(Point3::new(1.5, 0.5, -3.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(quad.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        let flat = Quad::new(
+            Point3::zeroes(),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 3.0),
+            HitRecord::default().material,
+        );
+        let bbox = flat.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.max().y > bbox.min().y);
+        assert_eq!(bbox.max().z, 3.0);
+    }
+}
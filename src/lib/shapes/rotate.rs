@@ -0,0 +1,170 @@
+//! This module defines the Rotate instance type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::utilities::degrees_to_radians;
+use crate::vector::{Point3, Vec3};
+use std::sync::Arc;
+
+/// An instance of a shape rotated around an arbitrary axis that passes through the origin.
+#[derive(Debug, Clone)]
+pub struct Rotate {
+    /// The shape being rotated.
+    object: Arc<HittableObjects>,
+    /// Unit vector of the rotation axis.
+    axis: Vec3,
+    /// Sine of the rotation angle.
+    sin_theta: f64,
+    /// Cosine of the rotation angle.
+    cos_theta: f64,
+}
+
+impl Rotate {
+    /// Creates and returns an owned instance of the shape rotated by 'angle' degrees around 'axis'.
+    pub fn new(object: HittableObjects, axis: Vec3, angle: f64) -> Self {
+        let radians = degrees_to_radians(angle);
+        Self {
+            object: Arc::new(object),
+            axis: axis.to_unit(),
+            sin_theta: radians.sin(),
+            cos_theta: radians.cos(),
+        }
+    }
+
+    /// Creates and returns an owned instance of the shape rotated by 'angle' degrees around X.
+    pub fn x(object: HittableObjects, angle: f64) -> Self {
+        Self::new(object, Vec3::new(1.0, 0.0, 0.0), angle)
+    }
+
+    /// Creates and returns an owned instance of the shape rotated by 'angle' degrees around Y.
+    pub fn y(object: HittableObjects, angle: f64) -> Self {
+        Self::new(object, Vec3::new(0.0, 1.0, 0.0), angle)
+    }
+
+    /// Creates and returns an owned instance of the shape rotated by 'angle' degrees around Z.
+    pub fn z(object: HittableObjects, angle: f64) -> Self {
+        Self::new(object, Vec3::new(0.0, 0.0, 1.0), angle)
+    }
+
+    // Rodrigues' rotation formula; a negative sine rotates in the opposite direction.
+    fn rotate(&self, v: Vec3, sin_theta: f64) -> Vec3 {
+        v * self.cos_theta
+            + Vec3::cross(self.axis, v) * sin_theta
+            + self.axis * Vec3::dot(self.axis, v) * (1.0 - self.cos_theta)
+    }
+
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        self.rotate(v, -self.sin_theta)
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        self.rotate(v, self.sin_theta)
+    }
+}
+
+impl Hittable for Rotate {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Rotate the Ray into object space, then rotate the hit point and normal back.
+        let rotated_ray = Ray::new(
+            self.to_object(ray.origin()),
+            self.to_object(ray.direction()),
+            ray.time(),
+        );
+        let mut hit_record = self.object.hit(&rotated_ray, t_min, t_max)?;
+        hit_record.p = self.to_world(hit_record.p);
+        hit_record.normal = self.to_world(hit_record.normal);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        let inner_box = self.object.bounding_box(time0, time1)?;
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
+
+        // Grow the box so it contains the eight rotated corners of the inner box.
+        for i in 0..2 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    let corner = Point3::new(
+                        if i == 0 {
+                            inner_box.min().x
+                        } else {
+                            inner_box.max().x
+                        },
+                        if j == 0 {
+                            inner_box.min().y
+                        } else {
+                            inner_box.max().y
+                        },
+                        if k == 0 {
+                            inner_box.min().z
+                        } else {
+                            inner_box.max().z
+                        },
+                    );
+                    let rotated = self.to_world(corner);
+                    min = Point3::new(
+                        min.x.min(rotated.x),
+                        min.y.min(rotated.y),
+                        min.z.min(rotated.z),
+                    );
+                    max = Point3::new(
+                        max.x.max(rotated.x),
+                        max.y.max(rotated.y),
+                        max.z.max(rotated.z),
+                    );
+                }
+            }
+        }
+        Some(AaBb::new(min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rotate;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::shapes::cuboid::Cuboid;
+    use crate::shapes::HittableObjects;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn rotated_cuboid_hit_and_box() {
+        // A 2x1x1 cuboid along X, stood up along Y by a quarter turn around Z.
+        let cuboid = HittableObjects::Cuboid(Cuboid::new(
+            Point3::zeroes(),
+            Point3::new(2.0, 1.0, 1.0),
+            HitRecord::default().material,
+        ));
+        let rotated = Rotate::z(cuboid, 90.0);
+
+        let ray = Ray::new(Point3::new(-0.5, 5.0, 0.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = rotated.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-9);
+        assert_eq!(hit.p, Point3::new(-0.5, 2.0, 0.5));
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit.front_face);
+
+        let bbox = rotated.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.min(), Point3::new(-1.0, 0.0, 0.0));
+        assert_eq!(bbox.max(), Point3::new(0.0, 2.0, 1.0));
+
+        // A diagonal turn makes the box grow to hold the rotated corners.
+        let cube = HittableObjects::Cuboid(Cuboid::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            HitRecord::default().material,
+        ));
+        let bbox = Rotate::y(cube, 45.0).bounding_box(0.0, 1.0).unwrap();
+        let half = 2.0_f64.sqrt();
+        assert_eq!(bbox.min(), Point3::new(-half, -1.0, -half));
+        assert_eq!(bbox.max(), Point3::new(half, 1.0, half));
+    }
+}
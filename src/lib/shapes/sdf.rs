@@ -0,0 +1,405 @@
+//! This module defines the signed distance field (SDF) shape, which is intersected by sphere
+//! tracing, and the tree of primitives and operators that describes its distance function.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::utilities::PI;
+use crate::vector::{Point3, Vec3};
+use std::fmt;
+use std::sync::Arc;
+
+/// Default maximum number of steps taken along a ray before it is considered a miss.
+pub const DEFAULT_MAX_STEPS: usize = 256;
+/// Default distance to the surface at which a ray is considered to hit it.
+pub const DEFAULT_EPSILON: f64 = 1e-4;
+
+/// A user supplied distance function, which must be negative inside the shape and must not
+/// overestimate the distance to its surface.
+#[derive(Clone)]
+pub struct DistanceFunction(Arc<dyn Fn(Point3) -> f64 + Send + Sync>);
+
+impl fmt::Debug for DistanceFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DistanceFunction")
+    }
+}
+
+/// Tree of primitives and operators that defines a signed distance field. Primitives are
+/// centered at the origin and placed with the Translate operator.
+#[derive(Debug, Clone)]
+pub enum SdfNode {
+    /// Sphere with a radius.
+    Sphere {
+        /// Radius of the sphere.
+        radius: f64,
+    },
+    /// Axis-aligned box.
+    Cuboid {
+        /// Half of the size of the box in each dimension.
+        half_size: Vec3,
+    },
+    /// Torus around the Y axis.
+    Torus {
+        /// Distance from the center to the middle of the tube.
+        major_radius: f64,
+        /// Radius of the tube.
+        minor_radius: f64,
+    },
+    /// Segment between two points, swept by a sphere.
+    Capsule {
+        /// First end of the segment.
+        a: Point3,
+        /// Second end of the segment.
+        b: Point3,
+        /// Radius of the sphere swept along the segment.
+        radius: f64,
+    },
+    /// A field moved by an offset.
+    Translate {
+        /// Offset applied to the field.
+        offset: Vec3,
+        /// The moved field.
+        node: Box<SdfNode>,
+    },
+    /// Points inside either field.
+    Union(Box<SdfNode>, Box<SdfNode>),
+    /// Points inside either field, blended where their surfaces are closer than the smoothness.
+    SmoothUnion {
+        /// Distance over which the surfaces are blended.
+        smoothness: f64,
+        /// First field.
+        left: Box<SdfNode>,
+        /// Second field.
+        right: Box<SdfNode>,
+    },
+    /// Points inside both fields.
+    Intersection(Box<SdfNode>, Box<SdfNode>),
+    /// Points inside the first field but not inside the second one.
+    Difference(Box<SdfNode>, Box<SdfNode>),
+    /// A field repeated infinitely in a grid of cells. A zero period disables the repetition
+    /// along that axis. The repeated field should fit inside half a cell.
+    Repeat {
+        /// Size of the cells along each axis.
+        period: Vec3,
+        /// The repeated field, centered in each cell.
+        node: Box<SdfNode>,
+    },
+    /// A field twisted around the Y axis.
+    Twist {
+        /// Angle of rotation in radians per unit along the Y axis.
+        rate: f64,
+        /// The twisted field.
+        node: Box<SdfNode>,
+    },
+    /// A user supplied distance function.
+    Custom(DistanceFunction),
+}
+
+impl SdfNode {
+    /// Creates a sphere of a radius at the origin.
+    pub fn sphere(radius: f64) -> Self {
+        SdfNode::Sphere { radius }
+    }
+
+    /// Creates an axis-aligned box at the origin from the half of its size.
+    pub fn cuboid(half_size: Vec3) -> Self {
+        SdfNode::Cuboid { half_size }
+    }
+
+    /// Creates a torus around the Y axis at the origin.
+    pub fn torus(major_radius: f64, minor_radius: f64) -> Self {
+        SdfNode::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    /// Creates a capsule around the segment between two points.
+    pub fn capsule(a: Point3, b: Point3, radius: f64) -> Self {
+        SdfNode::Capsule { a, b, radius }
+    }
+
+    /// Creates a field from a distance function.
+    pub fn custom<F>(function: F) -> Self
+    where
+        F: Fn(Point3) -> f64 + Send + Sync + 'static,
+    {
+        SdfNode::Custom(DistanceFunction(Arc::new(function)))
+    }
+
+    /// Returns this field moved by an offset.
+    pub fn translate(self, offset: Vec3) -> Self {
+        SdfNode::Translate {
+            offset,
+            node: Box::new(self),
+        }
+    }
+
+    /// Returns the union of this field and another one.
+    pub fn union(self, other: SdfNode) -> Self {
+        SdfNode::Union(Box::new(self), Box::new(other))
+    }
+
+    /// Returns the union of this field and another one, blended over a distance.
+    pub fn smooth_union(self, other: SdfNode, smoothness: f64) -> Self {
+        SdfNode::SmoothUnion {
+            smoothness,
+            left: Box::new(self),
+            right: Box::new(other),
+        }
+    }
+
+    /// Returns the intersection of this field and another one.
+    pub fn intersection(self, other: SdfNode) -> Self {
+        SdfNode::Intersection(Box::new(self), Box::new(other))
+    }
+
+    /// Returns this field minus another one.
+    pub fn difference(self, other: SdfNode) -> Self {
+        SdfNode::Difference(Box::new(self), Box::new(other))
+    }
+
+    /// Returns this field repeated in a grid of cells.
+    pub fn repeat(self, period: Vec3) -> Self {
+        SdfNode::Repeat {
+            period,
+            node: Box::new(self),
+        }
+    }
+
+    /// Returns this field twisted around the Y axis.
+    pub fn twist(self, rate: f64) -> Self {
+        SdfNode::Twist {
+            rate,
+            node: Box::new(self),
+        }
+    }
+
+    /// Returns the signed distance from a point to the surface, negative inside the shape.
+    pub fn distance(&self, p: Point3) -> f64 {
+        match self {
+            SdfNode::Sphere { radius } => p.length() - radius,
+            SdfNode::Cuboid { half_size } => {
+                let q = Vec3::new(
+                    p.x.abs() - half_size.x,
+                    p.y.abs() - half_size.y,
+                    p.z.abs() - half_size.z,
+                );
+                let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+                let inside = q.x.max(q.y).max(q.z).min(0.0);
+                outside + inside
+            }
+            SdfNode::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+                (ring * ring + p.y * p.y).sqrt() - minor_radius
+            }
+            SdfNode::Capsule { a, b, radius } => {
+                let pa = p - *a;
+                let ba = *b - *a;
+                let h = (Vec3::dot(pa, ba) / ba.length_squared()).clamp(0.0, 1.0);
+                (pa - ba * h).length() - radius
+            }
+            SdfNode::Translate { offset, node } => node.distance(p - *offset),
+            SdfNode::Union(left, right) => left.distance(p).min(right.distance(p)),
+            SdfNode::SmoothUnion {
+                smoothness,
+                left,
+                right,
+            } => {
+                // Polynomial smooth minimum.
+                let (a, b) = (left.distance(p), right.distance(p));
+                let h = (0.5 + 0.5 * (b - a) / smoothness).clamp(0.0, 1.0);
+                b + (a - b) * h - smoothness * h * (1.0 - h)
+            }
+            SdfNode::Intersection(left, right) => left.distance(p).max(right.distance(p)),
+            SdfNode::Difference(left, right) => left.distance(p).max(-right.distance(p)),
+            SdfNode::Repeat { period, node } => {
+                let wrap = |x: f64, period: f64| {
+                    if period > 0.0 {
+                        x - period * (x / period).round()
+                    } else {
+                        x
+                    }
+                };
+                node.distance(Vec3::new(
+                    wrap(p.x, period.x),
+                    wrap(p.y, period.y),
+                    wrap(p.z, period.z),
+                ))
+            }
+            SdfNode::Twist { rate, node } => {
+                let (sin, cos) = (rate * p.y).sin_cos();
+                let q = Vec3::new(cos * p.x - sin * p.z, p.y, sin * p.x + cos * p.z);
+                // Twisting stretches the field away from the axis, so scale the distance down
+                // by the local stretch to keep the steps from overshooting the surface.
+                let r = (p.x * p.x + p.z * p.z).sqrt();
+                node.distance(q) / (1.0 + (rate * r).powi(2)).sqrt()
+            }
+            SdfNode::Custom(function) => (function.0)(p),
+        }
+    }
+
+    /// Returns the unit gradient of the field at a point, by central differences.
+    fn gradient(&self, p: Point3, h: f64) -> Vec3 {
+        let dx = Vec3::new(h, 0.0, 0.0);
+        let dy = Vec3::new(0.0, h, 0.0);
+        let dz = Vec3::new(0.0, 0.0, h);
+        Vec3::new(
+            self.distance(p + dx) - self.distance(p - dx),
+            self.distance(p + dy) - self.distance(p - dy),
+            self.distance(p + dz) - self.distance(p - dz),
+        )
+        .to_unit()
+    }
+}
+
+/// A shape defined by a signed distance field inside a bounding box, intersected by marching
+/// along rays in steps of the distance to the surface.
+#[derive(Debug, Clone)]
+pub struct Sdf {
+    /// The distance function of the shape.
+    field: SdfNode,
+    /// Box that contains the whole surface; rays are only marched inside it.
+    bounds: AaBb,
+    /// Maximum number of steps taken along a ray.
+    max_steps: usize,
+    /// Distance to the surface at which a ray is considered to hit it.
+    epsilon: f64,
+    /// Material for the shape.
+    material: Materials,
+}
+
+impl Sdf {
+    /// Creates and returns an owned shape from a field and a box that contains its surface, with
+    /// the default step limit and epsilon.
+    pub fn new(field: SdfNode, bounds: AaBb, material: Materials) -> Self {
+        Self {
+            field,
+            bounds,
+            max_steps: DEFAULT_MAX_STEPS,
+            epsilon: DEFAULT_EPSILON,
+            material,
+        }
+    }
+
+    /// Returns the shape with a different step limit and epsilon. More steps and a smaller
+    /// epsilon give finer detail at the cost of rendering time.
+    pub fn with_limits(mut self, max_steps: usize, epsilon: f64) -> Self {
+        self.max_steps = max_steps;
+        self.epsilon = epsilon;
+        self
+    }
+}
+
+impl Hittable for Sdf {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (mut t, t_exit) = self.bounds.hit_interval(ray, t_min, t_max)?;
+        let speed = ray.direction().length();
+        let mut distance = self.field.distance(ray.at(t));
+        let mut steps = 0;
+
+        // A ray that enters the bounds from outside is outside the shape. Rays that start on
+        // the surface, like scattered ones, first move off it to find out whether they are
+        // leaving the shape or going into it; rays that start inside march to the surface from
+        // within.
+        let side = if t > t_min {
+            1.0
+        } else {
+            while distance.abs() < self.epsilon {
+                t += self.epsilon / speed;
+                steps += 1;
+                if steps >= self.max_steps || t > t_exit {
+                    return None;
+                }
+                distance = self.field.distance(ray.at(t));
+            }
+            distance.signum()
+        };
+        loop {
+            if side * distance < self.epsilon {
+                break;
+            }
+            t += side * distance / speed;
+            steps += 1;
+            if steps >= self.max_steps || t > t_exit {
+                return None;
+            }
+            distance = self.field.distance(ray.at(t));
+        }
+
+        let p = ray.at(t);
+        let outward_normal = self.field.gradient(p, self.epsilon);
+        // Spherical texture coordinates of the normal direction.
+        let u = ((-outward_normal.z).atan2(outward_normal.x) + PI) / (2.0 * PI);
+        let v = (-outward_normal.y).clamp(-1.0, 1.0).acos() / PI;
+        let mut hit_record =
+            HitRecord::new(p, Vec3::default(), self.material.clone(), t, u, v, false);
+        hit_record.set_face_normal(ray, outward_normal);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sdf, SdfNode};
+    use crate::aabb::AaBb;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn sdf_sphere_tracing_matches_analytic_sphere() {
+        let field = SdfNode::sphere(1.0).translate(Vec3::new(0.0, 0.0, 2.0));
+        let bounds = AaBb::new(Point3::new(-1.0, -1.0, 1.0), Point3::new(1.0, 1.0, 3.0));
+        let sdf = Sdf::new(field, bounds, HitRecord::default().material).with_limits(128, 1e-6);
+
+        let ray = Ray::new(Point3::zeroes(), Vec3::new(0.0, 0.0, 2.0), 0.0);
+        let hit = sdf.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-5);
+        assert!(hit.front_face);
+        assert!((hit.normal.z + 1.0).abs() < 1e-4);
+
+        // From inside the sphere the ray finds the far side.
+        let ray = Ray::new(Point3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let hit = sdf.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-5);
+        assert!(!hit.front_face);
+
+        let ray = Ray::new(Point3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(sdf.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn sdf_operators() {
+        let a = SdfNode::sphere(1.0).translate(Vec3::new(-0.9, 0.0, 0.0));
+        let b = SdfNode::sphere(1.0).translate(Vec3::new(0.9, 0.0, 0.0));
+        let union = a.clone().union(b.clone()).distance(Point3::zeroes());
+        let smooth = a.smooth_union(b, 0.5).distance(Point3::zeroes());
+        // The blend fills the crease between the spheres.
+        assert!(smooth < union);
+
+        let cells = SdfNode::sphere(0.5).repeat(Vec3::new(2.0, 0.0, 0.0));
+        assert!((cells.distance(Point3::new(10.0, 0.0, 0.0)) + 0.5).abs() < 1e-9);
+        assert!((cells.distance(Point3::new(0.0, 2.0, 0.0)) - 1.5).abs() < 1e-9);
+
+        // Twisting does not move points on the axis.
+        let bar = SdfNode::cuboid(Vec3::new(1.0, 3.0, 0.2)).twist(0.5);
+        assert!((bar.distance(Point3::new(0.0, 2.0, 0.0)) + 0.2).abs() < 1e-9);
+
+        let custom = SdfNode::custom(|p| p.y);
+        assert!((custom.distance(Point3::new(5.0, -2.0, 1.0)) + 2.0).abs() < 1e-12);
+    }
+}
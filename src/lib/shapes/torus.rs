@@ -0,0 +1,136 @@
+//! This module defines the Torus type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::frame::{angle_u, Frame};
+use crate::utilities::{solve_quartic, PI};
+use crate::vector::{Point3, Vec3};
+
+/// A ring torus around an axis through its center.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    /// Frame at the center, with the axis of the torus as its Y axis.
+    frame: Frame,
+    /// Distance from the center to the middle of the tube.
+    major_radius: f64,
+    /// Radius of the tube.
+    minor_radius: f64,
+    /// Material for the torus.
+    material: Materials,
+}
+
+impl Torus {
+    /// Creates and returns an owned torus. The 'u' texture coordinate goes around the axis and
+    /// 'v' goes around the tube.
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Materials,
+    ) -> Self {
+        Self {
+            frame: Frame::new(center, axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (o, d) = self.frame.local_ray(ray);
+        // Solve with a unit direction from the point of the ray closest to the center, which
+        // keeps the coefficients of the quartic small and the roots accurate.
+        let length = d.length();
+        let d = d / length;
+        let shift = -Vec3::dot(o, d);
+        let o = o + shift * d;
+
+        let r2 = self.major_radius * self.major_radius;
+        let od = Vec3::dot(o, d);
+        let k = o.length_squared() + r2 - self.minor_radius * self.minor_radius;
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2), with p = o + s d and |d| = 1.
+        let roots = solve_quartic(
+            1.0,
+            4.0 * od,
+            4.0 * od * od + 2.0 * k - 4.0 * r2 * (d.x * d.x + d.z * d.z),
+            4.0 * od * k - 8.0 * r2 * (o.x * d.x + o.z * d.z),
+            k * k - 4.0 * r2 * (o.x * o.x + o.z * o.z),
+        );
+        let (s, t) = roots
+            .into_iter()
+            .map(|s| (s, (s + shift) / length))
+            .find(|&(_, t)| t >= t_min && t <= t_max)?;
+
+        // The normal points away from the closest point on the circle in the middle of the tube.
+        let p = o + s * d;
+        let ring = (p.x * p.x + p.z * p.z).sqrt();
+        let center = Vec3::new(p.x, 0.0, p.z) * (self.major_radius / ring);
+        let normal = (p - center) / self.minor_radius;
+        let v = (p.y.atan2(ring - self.major_radius) + PI) / (2.0 * PI);
+
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            angle_u(p.x, p.z),
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, self.frame.world_vector(normal));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        let outer = self.major_radius + self.minor_radius;
+        let r = self.minor_radius;
+        Some(self.frame.bounding_box(
+            Point3::new(-outer, -r, -outer),
+            Point3::new(outer, r, outer),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Torus;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn torus_hits_the_tube_and_misses_the_hole() {
+        let torus = Torus::new(
+            Point3::new(0.0, 0.0, 10.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+            0.5,
+            HitRecord::default().material,
+        );
+
+        // Along the axis through the hole.
+        let hole = Ray::new(Point3::zeroes(), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(torus.hit(&hole, 0.001, f64::INFINITY).is_none());
+
+        // Through the middle of the tube, with an unnormalized direction.
+        let tube = Ray::new(Point3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0), 0.0);
+        let hit = torus.hit(&tube, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.p.z - 9.5).abs() < 1e-6);
+        assert!((hit.normal.z + 1.0).abs() < 1e-6);
+
+        // From the center, outwards in the plane of the torus, from inside the hole.
+        let out = Ray::new(Point3::new(0.0, 0.0, 10.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = torus.hit(&out, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 1.5).abs() < 1e-6);
+        assert!((hit.normal.x + 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,48 @@
+//! This module defines the Transformed instance type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::transform::Transform;
+use std::sync::Arc;
+
+/// An instance of a shape placed in the Scene by an arbitrary affine Transform.
+#[derive(Debug, Clone)]
+pub struct Transformed {
+    /// The shape being transformed.
+    object: Arc<HittableObjects>,
+    /// Transform that maps the shape from object space to world space. Kept behind an Arc so the
+    /// matrices do not inflate the size of every HittableObjects value.
+    transform: Arc<Transform>,
+}
+
+impl Transformed {
+    /// Creates and returns an owned instance of the shape with the Transform applied.
+    pub fn new(object: HittableObjects, transform: Transform) -> Self {
+        Self {
+            object: Arc::new(object),
+            transform: Arc::new(transform),
+        }
+    }
+
+    /// Returns the Transform of the instance.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.transform
+            .hit_through(self.object.as_ref(), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        let object_box = self.object.bounding_box(time0, time1)?;
+        Some(self.transform.apply_box(object_box))
+    }
+}
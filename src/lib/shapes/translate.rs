@@ -0,0 +1,83 @@
+//! This module defines the Translate instance type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::shapes::HittableObjects;
+use crate::vector::Vec3;
+use std::sync::Arc;
+
+/// An instance of a shape moved by an offset.
+#[derive(Debug, Clone)]
+pub struct Translate {
+    /// The shape being moved.
+    object: Arc<HittableObjects>,
+    /// Displacement applied to the shape.
+    offset: Vec3,
+}
+
+impl Translate {
+    /// Creates and returns an owned instance of the shape displaced by the offset.
+    pub fn new(object: HittableObjects, offset: Vec3) -> Self {
+        Self {
+            object: Arc::new(object),
+            offset,
+        }
+    }
+}
+
+impl Hittable for Translate {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Move the Ray instead of the shape, then move the hit point back.
+        let moved_ray = Ray::new(ray.origin() - self.offset, ray.direction(), ray.time());
+        let mut hit_record = self.object.hit(&moved_ray, t_min, t_max)?;
+        hit_record.p = hit_record.p + self.offset;
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        let inner_box = self.object.bounding_box(time0, time1)?;
+        Some(AaBb::new(
+            inner_box.min() + self.offset,
+            inner_box.max() + self.offset,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Translate;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::shapes::cuboid::Cuboid;
+    use crate::shapes::HittableObjects;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn translated_cuboid_hit_and_box() {
+        let cuboid = HittableObjects::Cuboid(Cuboid::new(
+            Point3::zeroes(),
+            Point3::new(1.0, 1.0, 1.0),
+            HitRecord::default().material,
+        ));
+        let moved = Translate::new(cuboid, Vec3::new(10.0, 0.0, -5.0));
+
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let ray = Ray::new(Point3::new(10.5, 5.0, -4.5), down, 0.0);
+        let hit = moved.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert_eq!(hit.p, Point3::new(10.5, 1.0, -4.5));
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        // The original place of the cuboid is empty.
+        let ray = Ray::new(Point3::new(0.5, 5.0, 0.5), down, 0.0);
+        assert!(moved.hit(&ray, 0.001, f64::INFINITY).is_none());
+
+        let bbox = moved.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.min(), Point3::new(10.0, 0.0, -5.0));
+        assert_eq!(bbox.max(), Point3::new(11.0, 1.0, -4.0));
+    }
+}
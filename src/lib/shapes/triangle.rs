@@ -0,0 +1,208 @@
+//! This module defines the Triangle type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// Determinants smaller than this are treated as a Ray parallel to the triangle.
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+/// A triangle with optional per-vertex normals (smooth shading) and per-vertex UV coordinates.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    /// The three vertices in counter-clockwise order.
+    vertices: [Point3; 3],
+    /// Optional per-vertex normals, interpolated for smooth shading.
+    normals: Option<[Vec3; 3]>,
+    /// Per-vertex texture coordinates.
+    uvs: [(f64, f64); 3],
+    /// Material for the triangle.
+    material: Materials,
+}
+
+impl Triangle {
+    /// Creates and returns an owned flat-shaded triangle. The UV coordinates of the hit are the
+    /// barycentric coordinates of the second and third vertices.
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, material: Materials) -> Self {
+        Self::with_attributes([v0, v1, v2], None, None, material)
+    }
+
+    /// Creates and returns an owned triangle with optional per-vertex normals and UVs.
+    pub fn with_attributes(
+        vertices: [Point3; 3],
+        normals: Option<[Vec3; 3]>,
+        uvs: Option<[(f64, f64); 3]>,
+        material: Materials,
+    ) -> Self {
+        Self {
+            vertices,
+            normals,
+            uvs: uvs.unwrap_or([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]),
+            material,
+        }
+    }
+
+    /// Returns the geometric (face) normal of the triangle, not normalized.
+    pub fn face_normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        Vec3::cross(v1 - v0, v2 - v0)
+    }
+}
+
+/// Intersects a Ray with the triangle (v0, v1, v2) using the Möller–Trumbore algorithm and
+/// returns the Ray parameter and the barycentric coordinates (b1, b2) of the hit point.
+pub(crate) fn intersect_triangle(
+    ray: &Ray,
+    vertices: &[Point3; 3],
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let [v0, v1, v2] = *vertices;
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+
+    let p_vec = Vec3::cross(ray.direction(), edge2);
+    let det = Vec3::dot(edge1, p_vec);
+    if det.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let t_vec = ray.origin() - v0;
+    let b1 = Vec3::dot(t_vec, p_vec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q_vec = Vec3::cross(t_vec, edge1);
+    let b2 = Vec3::dot(ray.direction(), q_vec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = Vec3::dot(edge2, q_vec) * inv_det;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+/// Builds the HitRecord of a triangle hit from the interpolated vertex attributes.
+pub(crate) fn triangle_hit_record(
+    ray: &Ray,
+    t: f64,
+    barycentric: (f64, f64),
+    face_normal: Vec3,
+    normals: Option<[Vec3; 3]>,
+    uvs: [(f64, f64); 3],
+    material: Materials,
+) -> HitRecord {
+    let (b1, b2) = barycentric;
+    let b0 = 1.0 - b1 - b2;
+    let u = b0 * uvs[0].0 + b1 * uvs[1].0 + b2 * uvs[2].0;
+    let v = b0 * uvs[0].1 + b1 * uvs[1].1 + b2 * uvs[2].1;
+
+    let mut hit_record = HitRecord::new(ray.at(t), Vec3::default(), material, t, u, v, false);
+    hit_record.set_face_normal(ray, face_normal.to_unit());
+
+    if let Some([n0, n1, n2]) = normals {
+        // Shade with the interpolated normal, kept on the same side as the geometric normal.
+        let shading_normal = (b0 * n0 + b1 * n1 + b2 * n2).to_unit();
+        hit_record.normal = if Vec3::dot(shading_normal, hit_record.normal) < 0.0 {
+            -shading_normal
+        } else {
+            shading_normal
+        };
+    }
+    hit_record
+}
+
+/// Computes the bounding box of three points, padded so that it is never flat.
+pub(crate) fn triangle_bounding_box(vertices: &[Point3; 3]) -> AaBb {
+    let [v0, v1, v2] = *vertices;
+    let min = Point3::new(
+        v0.x.min(v1.x).min(v2.x),
+        v0.y.min(v1.y).min(v2.y),
+        v0.z.min(v1.z).min(v2.z),
+    );
+    let max = Point3::new(
+        v0.x.max(v1.x).max(v2.x),
+        v0.y.max(v1.y).max(v2.y),
+        v0.z.max(v1.z).max(v2.z),
+    );
+    let padding = Vec3::new(BOX_PADDING, BOX_PADDING, BOX_PADDING);
+    AaBb::new(min - padding, max + padding)
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, b1, b2) = intersect_triangle(ray, &self.vertices, t_min, t_max)?;
+        Some(triangle_hit_record(
+            ray,
+            t,
+            (b1, b2),
+            self.face_normal(),
+            self.normals,
+            self.uvs,
+            self.material.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        Some(triangle_bounding_box(&self.vertices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Triangle;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    fn unit_triangle() -> Triangle {
+        Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            HitRecord::default().material,
+        )
+    }
+
+    #[test]
+    fn tri_hit_barycentric_uv() {
+        let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = unit_triangle().hit(&ray, 0.001, f64::INFINITY).unwrap();
+
+        assert_eq!(hit.p, Point3::new(0.25, 0.5, 0.0));
+        assert!((hit.t - 1.0).abs() < 1e-12);
+        assert!((hit.u - 0.25).abs() < 1e-12 && (hit.v - 0.5).abs() < 1e-12);
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn tri_miss_outside_and_parallel() {
+        let outside = Ray::new(Point3::new(0.75, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let parallel = Ray::new(Point3::new(0.1, 0.1, 1.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        assert!(unit_triangle()
+            .hit(&outside, 0.001, f64::INFINITY)
+            .is_none());
+        assert!(unit_triangle()
+            .hit(&parallel, 0.001, f64::INFINITY)
+            .is_none());
+    }
+
+    #[test]
+    fn tri_bounding_box_is_not_flat() {
+        let bbox = unit_triangle().bounding_box(0.0, 0.0).unwrap();
+
+        assert!(bbox.max().z - bbox.min().z > 0.0);
+    }
+}
@@ -0,0 +1,224 @@
+//! This module defines the Triangle Mesh type, whose triangles share a single set of vertex
+//! buffers, and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::linear_bvh::LinearBvh;
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::shapes::triangle::{intersect_triangle, triangle_bounding_box, triangle_hit_record};
+use crate::shapes::HittableObjects;
+use crate::vector::{Point3, Vec3};
+use std::sync::Arc;
+
+/// A triangle of a Mesh, expressed as indices into the buffers of the Mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshFace {
+    /// Indices of the three vertex positions.
+    pub positions: [usize; 3],
+    /// Optional indices of the three vertex normals.
+    pub normals: Option<[usize; 3]>,
+    /// Optional indices of the three vertex texture coordinates.
+    pub uvs: Option<[usize; 3]>,
+    /// Index of the material of the face in the materials of the Mesh.
+    pub material: usize,
+}
+
+/// Vertex buffers, faces and materials of a triangle mesh.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    /// Vertex positions.
+    pub positions: Vec<Point3>,
+    /// Vertex normals.
+    pub normals: Vec<Vec3>,
+    /// Vertex texture coordinates.
+    pub uvs: Vec<(f64, f64)>,
+    /// Optional vertex colors, one per position. When present the interpolated color tints the
+    /// material of the faces.
+    pub colors: Vec<Color>,
+    /// Triangles of the mesh.
+    pub faces: Vec<MeshFace>,
+    /// Materials referenced by the faces.
+    pub materials: Vec<Materials>,
+}
+
+impl Mesh {
+    /// Returns the three vertex positions of a face.
+    pub fn face_vertices(&self, face: usize) -> [Point3; 3] {
+        let [a, b, c] = self.faces[face].positions;
+        [self.positions[a], self.positions[b], self.positions[c]]
+    }
+}
+
+/// A single triangle of a shared Mesh.
+#[derive(Debug, Clone)]
+pub struct MeshTriangle {
+    /// The mesh that holds the vertex buffers.
+    mesh: Arc<Mesh>,
+    /// Index of the face in the mesh.
+    face: usize,
+}
+
+impl MeshTriangle {
+    /// Creates and returns an owned reference to a face of the mesh.
+    pub fn new(mesh: Arc<Mesh>, face: usize) -> Self {
+        Self { mesh, face }
+    }
+
+    /// Returns the index of the face in the mesh.
+    pub fn face(&self) -> usize {
+        self.face
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let vertices = self.mesh.face_vertices(self.face);
+        let (t, b1, b2) = intersect_triangle(ray, &vertices, t_min, t_max)?;
+
+        let face = &self.mesh.faces[self.face];
+        let normals = face.normals.map(|[a, b, c]| {
+            [
+                self.mesh.normals[a],
+                self.mesh.normals[b],
+                self.mesh.normals[c],
+            ]
+        });
+        let uvs = match face.uvs {
+            Some([a, b, c]) => [self.mesh.uvs[a], self.mesh.uvs[b], self.mesh.uvs[c]],
+            None => [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+        };
+        let face_normal = Vec3::cross(vertices[1] - vertices[0], vertices[2] - vertices[0]);
+        let mut hit_record = triangle_hit_record(
+            ray,
+            t,
+            (b1, b2),
+            face_normal,
+            normals,
+            uvs,
+            self.mesh.materials[face.material].clone(),
+        );
+        if !self.mesh.colors.is_empty() {
+            let [a, b, c] = face.positions;
+            let colors = &self.mesh.colors;
+            hit_record.tint = (1.0 - b1 - b2) * colors[a] + b1 * colors[b] + b2 * colors[c];
+        }
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        Some(triangle_bounding_box(&self.mesh.face_vertices(self.face)))
+    }
+}
+
+/// A triangle mesh wrapped in its own Bounding Volume Hierarchy so that it can be added to a
+/// HittableList as a single object.
+#[derive(Debug, Clone)]
+pub struct TriangleMesh {
+    /// The shared mesh data.
+    mesh: Arc<Mesh>,
+    /// Hierarchy over the triangles of the mesh.
+    bvh: LinearBvh,
+}
+
+impl TriangleMesh {
+    /// Creates and returns an owned triangle mesh and builds its hierarchy.
+    /// Panics if the mesh has no faces.
+    pub fn new(mesh: Mesh) -> Self {
+        assert!(!mesh.faces.is_empty(), "triangle mesh without faces");
+        let mesh = Arc::new(mesh);
+        let mut triangles = HittableList::default();
+        for face in 0..mesh.faces.len() {
+            triangles.add(HittableObjects::MeshTriangle(MeshTriangle::new(
+                Arc::clone(&mesh),
+                face,
+            )));
+        }
+        let bvh = LinearBvh::new(&triangles, 0.0, 1.0);
+
+        Self { mesh, bvh }
+    }
+
+    /// Creates a triangle mesh from a hierarchy over its triangles that was built before.
+    pub(crate) fn from_parts(mesh: Arc<Mesh>, bvh: LinearBvh) -> Self {
+        Self { mesh, bvh }
+    }
+
+    /// Returns the hierarchy over the triangles of the mesh.
+    pub(crate) fn bvh(&self) -> &LinearBvh {
+        &self.bvh
+    }
+
+    /// Returns the shared mesh data.
+    pub fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
+    }
+
+    /// Returns the number of triangles in the mesh.
+    pub fn total_triangles(&self) -> usize {
+        self.mesh.faces.len()
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        self.bvh.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mesh, MeshFace, TriangleMesh};
+    use crate::color::Color;
+    use crate::hittable::Hittable;
+    use crate::materials::metal::Metal;
+    use crate::materials::{Materials, Scatterable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn vertex_colors_tint_the_face_material() {
+        let mesh = Mesh {
+            positions: vec![
+                Point3::zeroes(),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 0.0, 1.0),
+            ],
+            colors: vec![Color::red(), Color::green(), Color::blue()],
+            faces: vec![MeshFace {
+                positions: [0, 1, 2],
+                normals: None,
+                uvs: None,
+                material: 0,
+            }],
+            materials: vec![Materials::Metals(Metal::new(
+                Color::new(0.5, 0.5, 0.5),
+                0.0,
+            ))],
+            ..Mesh::default()
+        };
+        let mesh = TriangleMesh::new(mesh);
+
+        // Straight down onto the first vertex, which is red.
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(matches!(hit.material, Materials::Metals(_)));
+        assert_eq!(hit.tint, Color::red());
+
+        let mut attenuation = Color::black();
+        let mut scattered = Ray::new(Point3::zeroes(), Vec3::zeroes(), 0.0);
+        assert!(hit
+            .material
+            .scatter(&ray, &hit, &mut attenuation, &mut scattered));
+        assert_eq!(attenuation, Color::new(0.5, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,121 @@
+//! This module defines the XY Rectangle type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// An axis-aligned rectangle in the XY plane located at z = k.
+#[derive(Debug, Clone)]
+pub struct XyRect {
+    /// Minimum X coordinate.
+    x0: f64,
+    /// Maximum X coordinate.
+    x1: f64,
+    /// Minimum Y coordinate.
+    y0: f64,
+    /// Maximum Y coordinate.
+    y1: f64,
+    /// Z coordinate of the plane of the rectangle.
+    k: f64,
+    /// Direction of the outward normal along the Z axis: 1 for +Z and -1 for -Z.
+    normal_sign: f64,
+    /// Material for the rectangle.
+    material: Materials,
+}
+
+impl XyRect {
+    /// Creates and returns an owned XY rectangle.
+    pub fn new(x0: f64, x1: f64, y0: f64, y1: f64, k: f64, material: Materials) -> Self {
+        Self {
+            x0,
+            x1,
+            y0,
+            y1,
+            k,
+            normal_sign: 1.0,
+            material,
+        }
+    }
+
+    /// Returns the rectangle with its outward normal pointing the other way along the Z axis.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal_sign: -self.normal_sign,
+            ..self
+        }
+    }
+}
+
+impl Hittable for XyRect {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - ray.origin().z) / ray.direction().z;
+        if t.is_nan() || t < t_min || t > t_max {
+            return None;
+        }
+
+        let x = ray.origin().x + t * ray.direction().x;
+        let y = ray.origin().y + t * ray.direction().y;
+        if x < self.x0 || x > self.x1 || y < self.y0 || y > self.y1 {
+            return None;
+        }
+
+        let u = (x - self.x0) / (self.x1 - self.x0);
+        let v = (y - self.y0) / (self.y1 - self.y0);
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            u,
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, Vec3::new(0.0, 0.0, self.normal_sign));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        // The bounding box must have non-zero width in each dimension, so pad the Z dimension.
+        Some(AaBb::new(
+            Point3::new(self.x0, self.y0, self.k - BOX_PADDING),
+            Point3::new(self.x1, self.y1, self.k + BOX_PADDING),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XyRect;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn xy_rect_hit_uv_and_padded_box() {
+        // A 2 by 4 rectangle at z = 1.
+        let rect = XyRect::new(0.0, 2.0, 0.0, 4.0, 1.0, HitRecord::default().material);
+        let ray = Ray::new(Point3::new(0.5, 3.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = rect.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!((hit.u - 0.25).abs() < 1e-9 && (hit.v - 0.75).abs() < 1e-9);
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        let outside = Ray::new(Point3::new(2.5, 3.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(rect.hit(&outside, 0.001, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(0.5, 3.0, 5.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(rect.hit(&parallel, 0.001, f64::INFINITY).is_none());
+
+        let bbox = rect.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().z < 1.0 && bbox.max().z > 1.0);
+        assert!(bbox.max().z - bbox.min().z < 0.001);
+        assert_eq!(bbox.max().x, 2.0);
+        assert_eq!(bbox.max().y, 4.0);
+    }
+}
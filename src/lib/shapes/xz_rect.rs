@@ -0,0 +1,121 @@
+//! This module defines the XZ Rectangle type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// An axis-aligned rectangle in the XZ plane located at y = k.
+#[derive(Debug, Clone)]
+pub struct XzRect {
+    /// Minimum X coordinate.
+    x0: f64,
+    /// Maximum X coordinate.
+    x1: f64,
+    /// Minimum Z coordinate.
+    z0: f64,
+    /// Maximum Z coordinate.
+    z1: f64,
+    /// Y coordinate of the plane of the rectangle.
+    k: f64,
+    /// Direction of the outward normal along the Y axis: 1 for +Y and -1 for -Y.
+    normal_sign: f64,
+    /// Material for the rectangle.
+    material: Materials,
+}
+
+impl XzRect {
+    /// Creates and returns an owned XZ rectangle.
+    pub fn new(x0: f64, x1: f64, z0: f64, z1: f64, k: f64, material: Materials) -> Self {
+        Self {
+            x0,
+            x1,
+            z0,
+            z1,
+            k,
+            normal_sign: 1.0,
+            material,
+        }
+    }
+
+    /// Returns the rectangle with its outward normal pointing the other way along the Y axis.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal_sign: -self.normal_sign,
+            ..self
+        }
+    }
+}
+
+impl Hittable for XzRect {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - ray.origin().y) / ray.direction().y;
+        if t.is_nan() || t < t_min || t > t_max {
+            return None;
+        }
+
+        let x = ray.origin().x + t * ray.direction().x;
+        let z = ray.origin().z + t * ray.direction().z;
+        if x < self.x0 || x > self.x1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+
+        let u = (x - self.x0) / (self.x1 - self.x0);
+        let v = (z - self.z0) / (self.z1 - self.z0);
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            u,
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, Vec3::new(0.0, self.normal_sign, 0.0));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        // The bounding box must have non-zero width in each dimension, so pad the Y dimension.
+        Some(AaBb::new(
+            Point3::new(self.x0, self.k - BOX_PADDING, self.z0),
+            Point3::new(self.x1, self.k + BOX_PADDING, self.z1),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XzRect;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn xz_rect_hit_uv_and_padded_box() {
+        // A 2 by 4 rectangle at y = 1.
+        let rect = XzRect::new(0.0, 2.0, 0.0, 4.0, 1.0, HitRecord::default().material);
+        let ray = Ray::new(Point3::new(0.5, 5.0, 3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = rect.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!((hit.u - 0.25).abs() < 1e-9 && (hit.v - 0.75).abs() < 1e-9);
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        let outside = Ray::new(Point3::new(2.5, 5.0, 3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(rect.hit(&outside, 0.001, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(0.5, 5.0, 3.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(rect.hit(&parallel, 0.001, f64::INFINITY).is_none());
+
+        let bbox = rect.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().y < 1.0 && bbox.max().y > 1.0);
+        assert!(bbox.max().y - bbox.min().y < 0.001);
+        assert_eq!(bbox.max().x, 2.0);
+        assert_eq!(bbox.max().z, 4.0);
+    }
+}
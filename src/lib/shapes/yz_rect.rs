@@ -0,0 +1,121 @@
+//! This module defines the YZ Rectangle type and its implementation of the Hittable trait.
+
+#![warn(missing_docs)]
+#![allow(missing_debug_implementations)]
+
+use crate::aabb::{AaBb, BOX_PADDING};
+use crate::hittable::{HitRecord, Hittable};
+use crate::materials::Materials;
+use crate::ray::Ray;
+use crate::vector::{Point3, Vec3};
+
+/// An axis-aligned rectangle in the YZ plane located at x = k.
+#[derive(Debug, Clone)]
+pub struct YzRect {
+    /// Minimum Y coordinate.
+    y0: f64,
+    /// Maximum Y coordinate.
+    y1: f64,
+    /// Minimum Z coordinate.
+    z0: f64,
+    /// Maximum Z coordinate.
+    z1: f64,
+    /// X coordinate of the plane of the rectangle.
+    k: f64,
+    /// Direction of the outward normal along the X axis: 1 for +X and -1 for -X.
+    normal_sign: f64,
+    /// Material for the rectangle.
+    material: Materials,
+}
+
+impl YzRect {
+    /// Creates and returns an owned YZ rectangle.
+    pub fn new(y0: f64, y1: f64, z0: f64, z1: f64, k: f64, material: Materials) -> Self {
+        Self {
+            y0,
+            y1,
+            z0,
+            z1,
+            k,
+            normal_sign: 1.0,
+            material,
+        }
+    }
+
+    /// Returns the rectangle with its outward normal pointing the other way along the X axis.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal_sign: -self.normal_sign,
+            ..self
+        }
+    }
+}
+
+impl Hittable for YzRect {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - ray.origin().x) / ray.direction().x;
+        if t.is_nan() || t < t_min || t > t_max {
+            return None;
+        }
+
+        let y = ray.origin().y + t * ray.direction().y;
+        let z = ray.origin().z + t * ray.direction().z;
+        if y < self.y0 || y > self.y1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+
+        let u = (y - self.y0) / (self.y1 - self.y0);
+        let v = (z - self.z0) / (self.z1 - self.z0);
+        let mut hit_record = HitRecord::new(
+            ray.at(t),
+            Vec3::default(),
+            self.material.clone(),
+            t,
+            u,
+            v,
+            false,
+        );
+        hit_record.set_face_normal(ray, Vec3::new(self.normal_sign, 0.0, 0.0));
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AaBb> {
+        // The bounding box must have non-zero width in each dimension, so pad the X dimension.
+        Some(AaBb::new(
+            Point3::new(self.k - BOX_PADDING, self.y0, self.z0),
+            Point3::new(self.k + BOX_PADDING, self.y1, self.z1),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::YzRect;
+    use crate::hittable::{HitRecord, Hittable};
+    use crate::ray::Ray;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn yz_rect_hit_uv_and_padded_box() {
+        // A 2 by 4 rectangle at x = 1.
+        let rect = YzRect::new(0.0, 2.0, 0.0, 4.0, 1.0, HitRecord::default().material);
+        let ray = Ray::new(Point3::new(5.0, 0.5, 3.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = rect.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!((hit.u - 0.25).abs() < 1e-9 && (hit.v - 0.75).abs() < 1e-9);
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vec3::new(1.0, 0.0, 0.0));
+
+        let outside = Ray::new(Point3::new(5.0, 2.5, 3.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        assert!(rect.hit(&outside, 0.001, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(5.0, 0.5, 3.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        assert!(rect.hit(&parallel, 0.001, f64::INFINITY).is_none());
+
+        let bbox = rect.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().x < 1.0 && bbox.max().x > 1.0);
+        assert!(bbox.max().x - bbox.min().x < 0.001);
+        assert_eq!(bbox.max().y, 2.0);
+        assert_eq!(bbox.max().z, 4.0);
+    }
+}
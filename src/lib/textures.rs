@@ -1,12 +1,14 @@
 //! The Textures module that contains the Texture trait and the list of textures supported..
 
 pub mod checker;
+pub mod image_texture;
 pub mod noise;
 pub mod perlin;
 pub mod solid_color;
 
 use crate::color::Color;
 use crate::textures::checker::Checker;
+use crate::textures::image_texture::ImageTexture;
 use crate::textures::noise::Noise;
 use crate::textures::solid_color::SolidColor;
 use crate::vector::Point3;
@@ -26,6 +28,8 @@ pub enum Texture {
     Checker(Checker),
     /// The noise texture variant.
     Noise(Noise),
+    /// The image texture variant.
+    Image(ImageTexture),
 }
 
 impl Textures for Texture {
@@ -34,6 +38,7 @@ impl Textures for Texture {
             Texture::SolidColor(solid_color) => solid_color.value(u, v, p),
             Texture::Checker(checker) => checker.value(u, v, p),
             Texture::Noise(noise) => noise.value(u, v, p),
+            Texture::Image(image) => image.value(u, v, p),
         }
     }
 }
@@ -0,0 +1,246 @@
+//! The image texture.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::color::Color;
+use crate::textures::Textures;
+use crate::vector::Point3;
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Largest number of pixels of a decoded image, which keeps a corrupt header from requesting an
+/// allocation of many gigabytes.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// Image texture type that maps u,v coordinates onto the pixels of an image.
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    /// Width of the image in pixels.
+    width: usize,
+    /// Height of the image in pixels.
+    height: usize,
+    /// Pixels in rows from top to bottom, shared between clones of the texture.
+    pixels: Arc<Vec<Color>>,
+}
+
+impl ImageTexture {
+    /// Creates and returns a new owned image texture from pixels in rows from top to bottom.
+    /// Panics if the image is empty.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "image without pixels");
+        assert_eq!(pixels.len(), width * height, "wrong number of pixels");
+        Self {
+            width,
+            height,
+            pixels: Arc::new(pixels),
+        }
+    }
+
+    /// Loads a PNG or a Netpbm (PPM/PGM) image and returns a new owned image texture.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut data = Vec::new();
+        BufReader::new(File::open(path)?).read_to_end(&mut data)?;
+        Self::decode(&data, &path.display().to_string())
+    }
+
+    /// Decodes a PNG or a Netpbm (PPM/PGM) image held in memory and returns a new owned image
+    /// texture. The name is only used to report errors.
+    pub fn decode(data: &[u8], name: &str) -> Result<Self, Error> {
+        let invalid =
+            |message: &str| Error::new(ErrorKind::InvalidData, format!("{}: {}", name, message));
+        if data.starts_with(b"\x89PNG") {
+            Self::decode_png(data).map_err(|err| invalid(&err))
+        } else if data.starts_with(b"P") {
+            Self::decode_netpbm(data).map_err(|err| invalid(&err))
+        } else {
+            Err(invalid("unsupported image format, expected PNG or PPM/PGM"))
+        }
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns a new texture whose pixels are the pixels of this one multiplied by a tint.
+    pub fn tinted(&self, tint: Color) -> Self {
+        let pixels = self.pixels.iter().map(|&pixel| pixel * tint).collect();
+        Self::new(self.width, self.height, pixels)
+    }
+
+    /// Returns the color of the pixel at column x and row y, counted from the top.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Returns the number of pixels of an image, or an error if it is empty or too large.
+    fn pixel_count(width: usize, height: usize) -> Result<usize, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("image without pixels ({}x{})", width, height));
+        }
+        width
+            .checked_mul(height)
+            .filter(|&count| count <= MAX_PIXELS)
+            .ok_or_else(|| format!("image too large ({}x{})", width, height))
+    }
+
+    fn decode_png(data: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let (width, height) = reader.info().size();
+        Self::pixel_count(width as usize, height as usize)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        let channels = info.color_type.samples();
+
+        let pixels = buffer[..info.buffer_size()]
+            .chunks(channels)
+            .map(|px| match channels {
+                1 | 2 => Self::to_color(px[0], px[0], px[0]),
+                _ => Self::to_color(px[0], px[1], px[2]),
+            })
+            .collect();
+        Ok(Self::new(info.width as usize, info.height as usize, pixels))
+    }
+
+    // Supports the plain (P2, P3) and binary (P5, P6) variants of PGM and PPM.
+    fn decode_netpbm(data: &[u8]) -> Result<Self, String> {
+        let malformed = || "malformed Netpbm image".to_string();
+        let mut pos = 0;
+        let next_token = |pos: &mut usize| -> Option<String> {
+            loop {
+                while *pos < data.len() && data[*pos].is_ascii_whitespace() {
+                    *pos += 1;
+                }
+                if *pos < data.len() && data[*pos] == b'#' {
+                    while *pos < data.len() && data[*pos] != b'\n' {
+                        *pos += 1;
+                    }
+                    continue;
+                }
+                break;
+            }
+            let start = *pos;
+            while *pos < data.len() && !data[*pos].is_ascii_whitespace() {
+                *pos += 1;
+            }
+            (start < *pos).then(|| String::from_utf8_lossy(&data[start..*pos]).to_string())
+        };
+
+        let number = |pos: &mut usize| -> Result<usize, String> {
+            next_token(pos)
+                .and_then(|token| token.parse().ok())
+                .ok_or_else(malformed)
+        };
+        let magic = next_token(&mut pos).ok_or_else(malformed)?;
+        let width = number(&mut pos)?;
+        let height = number(&mut pos)?;
+        let max_value = number(&mut pos)?;
+        if max_value == 0 || max_value > 255 {
+            return Err(malformed());
+        }
+        let channels = match magic.as_str() {
+            "P2" | "P5" => 1,
+            "P3" | "P6" => 3,
+            _ => return Err(malformed()),
+        };
+        let sample_count = Self::pixel_count(width, height)?
+            .checked_mul(channels)
+            .ok_or_else(malformed)?;
+
+        let samples: Vec<usize> = if magic == "P5" || magic == "P6" {
+            // A single whitespace separates the header from the binary samples.
+            let start = pos + 1;
+            data.get(start..start + sample_count)
+                .ok_or_else(malformed)?
+                .iter()
+                .map(|&s| s as usize)
+                .collect()
+        } else {
+            (0..sample_count)
+                .map(|_| number(&mut pos))
+                .collect::<Result<_, _>>()?
+        };
+
+        let pixels = samples
+            .chunks(channels)
+            .map(|px| {
+                let (r, g, b) = if channels == 1 {
+                    (px[0], px[0], px[0])
+                } else {
+                    (px[0], px[1], px[2])
+                };
+                Color::new(r as f64, g as f64, b as f64) / max_value as f64
+            })
+            .collect();
+        Ok(Self::new(width, height, pixels))
+    }
+
+    fn to_color(r: u8, g: u8, b: u8) -> Color {
+        Color::new(r as f64, g as f64, b as f64) / 255.0
+    }
+}
+
+impl Textures for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        // Wrap input texture coordinates to [0,1) so the image repeats, as on a Plane. V is
+        // flipped to image coordinates.
+        let u = u.rem_euclid(1.0);
+        let v = 1.0 - v.rem_euclid(1.0);
+
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixel(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImageTexture;
+    use crate::color::Color;
+    use crate::textures::Textures;
+    use crate::vector::Point3;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn image_decodes_netpbm_and_rejects_bad_sizes() {
+        let image = ImageTexture::decode(b"P3 2 1 255 255 0 0 0 0 255", "ok.ppm").unwrap();
+        assert_eq!(
+            image.value(0.9, 0.5, Point3::zeroes()),
+            Color::new(0.0, 0.0, 1.0)
+        );
+
+        for data in [
+            "P2 0 4 255".to_string(),
+            "P5 4 0 255 ".to_string(),
+            format!("P6 {} {} 255 ", usize::MAX, usize::MAX),
+            "P5 100000 100000 255 ".to_string(),
+        ] {
+            let err = ImageTexture::decode(data.as_bytes(), "bad.pgm")
+                .err()
+                .unwrap();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn image_repeats_outside_the_unit_square() {
+        let image = ImageTexture::decode(b"P3 2 1 255 255 0 0 0 0 255", "ok.ppm").unwrap();
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+
+        assert_eq!(image.value(1.25, 0.5, Point3::zeroes()), red);
+        assert_eq!(image.value(1.75, 0.5, Point3::zeroes()), blue);
+        assert_eq!(image.value(-0.25, 2.5, Point3::zeroes()), blue);
+    }
+}
@@ -1,6 +1,5 @@
 //! The perlin texture.
 
-use crate::utilities::{random_float, random_usize_range};
 use crate::vector::{Point3, Vec3};
 use rand::Rng;
 
@@ -16,12 +15,13 @@ pub struct Perlin {
 impl Perlin {
     const POINT_COUNT: usize = 256;
 
+    /// Turbulence: sum of repeated calls to noise at increasing frequencies.
     pub fn turbulence(&self, p: Point3, depth: usize) -> f64 {
         let mut accum = 0.0f64;
         let mut temp_p = p;
         let mut weight = 1.0;
 
-        for i in 0..depth {
+        for _ in 0..depth {
             accum += weight * self.noise(temp_p);
             weight *= 0.5;
             temp_p = temp_p * 2.0;
@@ -41,9 +41,9 @@ impl Perlin {
 
     /// Noise function
     pub fn noise(&self, p: Point3) -> f64 {
-        let mut u = p.x - p.x.floor();
-        let mut v = p.y - p.y.floor();
-        let mut w = p.z - p.z.floor();
+        let u = p.x - p.x.floor();
+        let v = p.y - p.y.floor();
+        let w = p.z - p.z.floor();
 
         // u = u * u * (3.0 - 2.0 * u);
         // v = v * v * (3.0 - 2.0 * v);
@@ -53,16 +53,15 @@ impl Perlin {
         let j = p.y.floor() as usize;
         let k = p.z.floor() as usize;
 
-        let mut c = [[[Vec3::new(0.0, 0.0, 0.0); 2]; 2]; 2];
-        for di in 0..2 {
-            for dj in 0..2 {
-                for dk in 0..2 {
-                    c[di][dj][dk] = self.ranvec[self.perm_x[(i + di) & 255]
+        let c: [[[Vec3; 2]; 2]; 2] = std::array::from_fn(|di| {
+            std::array::from_fn(|dj| {
+                std::array::from_fn(|dk| {
+                    self.ranvec[self.perm_x[(i + di) & 255]
                         ^ self.perm_y[(j + dj) & 255]
                         ^ self.perm_z[(k + dk) & 255]]
-                }
-            }
-        }
+                })
+            })
+        });
         Self::trilinear_interp(&c, u, v, w)
     }
 
@@ -71,14 +70,15 @@ impl Perlin {
         let vv = v * v * (3.0 - 2.0 * v);
         let ww = w * w * (3.0 - 2.0 * w);
         let mut accum = 0.0;
-        for i in 0..2 {
-            for j in 0..2 {
-                for k in 0..2 {
-                    let weight = Vec3::new(u - i as f64, v - j as f64, w - k as f64);
-                    accum += (i as f64 * uu + (1.0 - i as f64) * (1.0 - uu))
-                        * (j as f64 * vv + (1.0 - j as f64) * (1.0 - vv))
-                        * (k as f64 * ww + (1.0 - k as f64) * (1.0 - ww))
-                        * Vec3::dot(c[i][j][k], weight);
+        for (i, plane) in c.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let (i, j, k) = (i as f64, j as f64, k as f64);
+                    let weight = Vec3::new(u - i, v - j, w - k);
+                    accum += (i * uu + (1.0 - i) * (1.0 - uu))
+                        * (j * vv + (1.0 - j) * (1.0 - vv))
+                        * (k * ww + (1.0 - k) * (1.0 - ww))
+                        * Vec3::dot(*gradient, weight);
                 }
             }
         }
@@ -111,7 +111,7 @@ impl Perlin {
         let mut rng = rand::thread_rng();
         rng.gen_range(0.0..=1.0);
         for i in (1..n).rev() {
-            let target = rng.gen_range(0..=(i + 1));
+            let target = rng.gen_range(0..=i);
             p.swap(i, target);
         }
         p.to_vec()
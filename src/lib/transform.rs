@@ -0,0 +1,588 @@
+//! The transform module implements the functionality for 4x4 affine matrices (Mat4) and the
+//! Transform type, used to scale, rotate and translate points, vectors and normals.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::utilities::degrees_to_radians;
+use crate::vector::{Point3, Vec3};
+use std::ops::Mul;
+
+/// Type representing a 4x4 matrix in row-major order.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mat4 {
+    /// Elements of the matrix, indexed as m[row][column].
+    pub m: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    /// Function creates a new matrix from its rows.
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    /// Function returns the identity matrix.
+    pub fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Function returns a matrix that translates points by the offset.
+    pub fn translation(offset: Vec3) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Function returns a matrix that scales each coordinate by the corresponding factor.
+    pub fn scaling(factors: Vec3) -> Self {
+        Self::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Function returns a matrix that rotates by 'angle' degrees around an axis through the origin.
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        let a = axis.to_unit();
+        let radians = degrees_to_radians(angle);
+        let (sin, cos) = radians.sin_cos();
+        let t = 1.0 - cos;
+
+        Self::new([
+            [
+                t * a.x * a.x + cos,
+                t * a.x * a.y - sin * a.z,
+                t * a.x * a.z + sin * a.y,
+                0.0,
+            ],
+            [
+                t * a.x * a.y + sin * a.z,
+                t * a.y * a.y + cos,
+                t * a.y * a.z - sin * a.x,
+                0.0,
+            ],
+            [
+                t * a.x * a.z - sin * a.y,
+                t * a.y * a.z + sin * a.x,
+                t * a.z * a.z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Function returns the transpose of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = self.m[col][row];
+            }
+        }
+        Self::new(m)
+    }
+
+    /// Function returns the inverse of the matrix, or None if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting on the augmented matrix [M | I].
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap_or(col);
+            if a[pivot][col].abs() < f64::EPSILON {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for k in 0..4 {
+                a[col][k] *= scale;
+                inv[col][k] *= scale;
+            }
+
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for k in 0..4 {
+                        a[row][k] -= factor * a[col][k];
+                        inv[row][k] -= factor * inv[col][k];
+                    }
+                }
+            }
+        }
+        Some(Self::new(inv))
+    }
+
+    /// Function applies the matrix to a point, including its translation.
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x, y, z) / w
+        }
+    }
+
+    /// Function applies the matrix to a vector, ignoring its translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::identity()
+    }
+}
+
+impl Mul<Mat4> for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[row][k] * rhs.m[k][col]).sum();
+            }
+        }
+        Self::new(m)
+    }
+}
+
+/// Type representing a rotation as a unit quaternion.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    /// Component along the X axis of the vector part.
+    pub x: f64,
+    /// Component along the Y axis of the vector part.
+    pub y: f64,
+    /// Component along the Z axis of the vector part.
+    pub z: f64,
+    /// Scalar part.
+    pub w: f64,
+}
+
+impl Quaternion {
+    /// Function creates a new quaternion from its components, normalized to a rotation.
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+        let length = (x * x + y * y + z * z + w * w).sqrt();
+        Self {
+            x: x / length,
+            y: y / length,
+            z: z / length,
+            w: w / length,
+        }
+    }
+
+    /// Function returns the quaternion of no rotation.
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Function returns the rotation by 'angle' degrees around an axis through the origin.
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let a = axis.to_unit();
+        let (sin, cos) = (degrees_to_radians(angle) / 2.0).sin_cos();
+        Self::new(a.x * sin, a.y * sin, a.z * sin, cos)
+    }
+
+    /// Function returns the inverse rotation.
+    pub fn conjugate(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w,
+        }
+    }
+
+    /// Function rotates a vector.
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        // v + 2w (q x v) + 2 q x (q x v), with q the vector part.
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = 2.0 * Vec3::cross(q, v);
+        v + self.w * t + Vec3::cross(q, t)
+    }
+
+    /// Function interpolates along the shortest arc between two rotations, from this one at
+    /// t = 0 to 'other' at t = 1, at constant angular speed.
+    pub fn slerp(self, other: Quaternion, t: f64) -> Self {
+        let mut cos = self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w;
+        // q and -q are the same rotation; pick the one on the shortest arc.
+        let other = if cos < 0.0 {
+            cos = -cos;
+            Quaternion {
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+                w: -other.w,
+            }
+        } else {
+            other
+        };
+        let (a, b) = if cos > 0.9995 {
+            // Nearly equal rotations, where linear interpolation is accurate and stable.
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        Self::new(
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+            a * self.w + b * other.w,
+        )
+    }
+
+    /// Function returns the rotation matrix of the quaternion.
+    pub fn rotation_matrix(self) -> Mat4 {
+        let Quaternion { x, y, z, w } = self;
+        Mat4::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+/// Type representing an invertible affine transformation together with its inverse and the
+/// inverse-transpose used to transform normals.
+#[derive(Debug, Copy, Clone)]
+pub struct Transform {
+    /// Matrix that maps object space to world space.
+    matrix: Mat4,
+    /// Matrix that maps world space to object space.
+    inverse: Mat4,
+    /// Inverse-transpose of the matrix, used to map normals to world space.
+    normal_matrix: Mat4,
+}
+
+impl Transform {
+    /// Function creates a new Transform from an invertible matrix. Panics if the matrix is
+    /// singular; use 'try_new' for matrices built from input data.
+    pub fn new(matrix: Mat4) -> Self {
+        Self::try_new(matrix).expect("Transform matrix must be invertible")
+    }
+
+    /// Function creates a new Transform from a matrix, or returns None if it is singular, such
+    /// as a scaling by zero.
+    pub fn try_new(matrix: Mat4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+        Some(Self::with_inverse(matrix, inverse))
+    }
+
+    /// Function creates a new Transform from a matrix and its known inverse, skipping the
+    /// general inversion. The caller must make sure that 'inverse' is the inverse of 'matrix'.
+    pub(crate) fn with_inverse(matrix: Mat4, inverse: Mat4) -> Self {
+        Self {
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+        }
+    }
+
+    /// Function returns the identity Transform.
+    pub fn identity() -> Self {
+        Self::new(Mat4::identity())
+    }
+
+    /// Function returns a Transform that translates by the offset.
+    pub fn translate(offset: Vec3) -> Self {
+        Self::new(Mat4::translation(offset))
+    }
+
+    /// Function returns a Transform that scales each coordinate by the corresponding factor.
+    /// Panics if a factor is zero.
+    pub fn scale(factors: Vec3) -> Self {
+        Self::new(Mat4::scaling(factors))
+    }
+
+    /// Function returns a Transform that rotates by 'angle' degrees around an axis.
+    pub fn rotate(axis: Vec3, angle: f64) -> Self {
+        Self::new(Mat4::rotation(axis, angle))
+    }
+
+    /// Function returns a Transform that applies this Transform first and then 'next'.
+    pub fn then(&self, next: &Transform) -> Self {
+        Self {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+            normal_matrix: next.normal_matrix * self.normal_matrix,
+        }
+    }
+
+    /// Function returns the inverse Transform.
+    pub fn inverted(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+            normal_matrix: self.matrix.transpose(),
+        }
+    }
+
+    /// Function returns the object-to-world matrix.
+    pub fn matrix(&self) -> Mat4 {
+        self.matrix
+    }
+
+    /// Function returns the world-to-object matrix.
+    pub fn inverse(&self) -> Mat4 {
+        self.inverse
+    }
+
+    /// Function maps a point from object space to world space.
+    pub fn apply_point(&self, p: Point3) -> Point3 {
+        self.matrix.transform_point(p)
+    }
+
+    /// Function maps a vector from object space to world space.
+    pub fn apply_vector(&self, v: Vec3) -> Vec3 {
+        self.matrix.transform_vector(v)
+    }
+
+    /// Function maps a normal from object space to world space. The result is not normalized.
+    pub fn apply_normal(&self, n: Vec3) -> Vec3 {
+        self.normal_matrix.transform_vector(n)
+    }
+
+    /// Function maps a point from world space to object space.
+    pub fn invert_point(&self, p: Point3) -> Point3 {
+        self.inverse.transform_point(p)
+    }
+
+    /// Function maps a vector from world space to object space.
+    pub fn invert_vector(&self, v: Vec3) -> Vec3 {
+        self.inverse.transform_vector(v)
+    }
+
+    /// Function intersects a world-space Ray with an object placed by this Transform. The Ray is
+    /// mapped to object space, and the hit point and normal are mapped back to world space.
+    pub fn hit_through<H: Hittable + ?Sized>(
+        &self,
+        object: &H,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        // The direction is not normalized, so 't' is the same in object and world space.
+        let object_ray = Ray::new(
+            self.invert_point(ray.origin()),
+            self.invert_vector(ray.direction()),
+            ray.time(),
+        );
+        let mut hit_record = object.hit(&object_ray, t_min, t_max)?;
+        hit_record.p = self.apply_point(hit_record.p);
+        hit_record.normal = self.apply_normal(hit_record.normal).to_unit();
+
+        Some(hit_record)
+    }
+
+    /// Function returns the world-space bounding box of a transformed object-space box.
+    pub fn apply_box(&self, object_box: AaBb) -> AaBb {
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
+
+        for corner in 0..8 {
+            let p = self.apply_point(Point3::new(
+                if corner & 1 == 0 {
+                    object_box.min().x
+                } else {
+                    object_box.max().x
+                },
+                if corner & 2 == 0 {
+                    object_box.min().y
+                } else {
+                    object_box.max().y
+                },
+                if corner & 4 == 0 {
+                    object_box.min().z
+                } else {
+                    object_box.max().z
+                },
+            ));
+            min = Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        AaBb::new(min, max)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Mul<Transform> for Transform {
+    type Output = Self;
+
+    /// Composes two Transforms; the right-hand side is applied first.
+    fn mul(self, rhs: Transform) -> Self::Output {
+        rhs.then(&self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mat4, Quaternion, Transform};
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn mat_inverse_times_matrix_is_identity() {
+        let m = Mat4::new([
+            [2.0, 0.0, 1.0, 3.0],
+            [1.0, 3.0, 0.0, -1.0],
+            [0.0, 1.0, 4.0, 2.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let product = m * m.inverse().unwrap();
+
+        for row in 0..4 {
+            for col in 0..4 {
+                let expected = if row == col { 1.0 } else { 0.0 };
+                assert!((product.m[row][col] - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn mat_singular_has_no_inverse() {
+        let flat = Mat4::scaling(Vec3::new(1.0, 0.0, 1.0));
+        assert!(flat.inverse().is_none());
+        assert!(Transform::try_new(flat).is_none());
+        assert!(Transform::try_new(Mat4::identity()).is_some());
+    }
+
+    #[test]
+    fn transform_point_and_vector() {
+        let t = Transform::translate(Vec3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(
+            t.apply_point(Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(2.0, 3.0, 4.0)
+        );
+        assert_eq!(
+            t.apply_vector(Vec3::new(1.0, 1.0, 1.0)),
+            Vec3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn transform_rotation() {
+        let t = Transform::rotate(Vec3::new(0.0, 1.0, 0.0), 90.0);
+
+        assert_eq!(
+            t.apply_point(Point3::new(1.0, 0.0, 0.0)),
+            Point3::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            t.invert_point(Point3::new(0.0, 0.0, -1.0)),
+            Point3::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn transform_normal_stays_perpendicular() {
+        let t = Transform::scale(Vec3::new(1.0, 4.0, 1.0));
+        // Surface spanned by the tangent (1, -1, 0) with normal (1, 1, 0).
+        let tangent = t.apply_vector(Vec3::new(1.0, -1.0, 0.0));
+        let normal = t.apply_normal(Vec3::new(1.0, 1.0, 0.0));
+
+        assert!(Vec3::dot(tangent, normal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn transform_composition_order() {
+        let scale = Transform::scale(Vec3::new(2.0, 2.0, 2.0));
+        let translate = Transform::translate(Vec3::new(1.0, 0.0, 0.0));
+        let t = scale.then(&translate);
+
+        assert_eq!(
+            t.apply_point(Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(3.0, 2.0, 2.0)
+        );
+        assert_eq!(
+            (translate * scale).apply_point(Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(3.0, 2.0, 2.0)
+        );
+        assert_eq!(
+            t.invert_point(Point3::new(3.0, 2.0, 2.0)),
+            Point3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn quaternion_matches_matrix_rotation_and_slerps() {
+        let axis = Vec3::new(1.0, 2.0, -0.5);
+        let q = Quaternion::from_axis_angle(axis, 70.0);
+        let m = Mat4::rotation(axis, 70.0);
+        let v = Vec3::new(0.3, -1.2, 2.0);
+
+        assert_eq!(q.rotate(v), m.transform_vector(v));
+        assert_eq!(
+            q.rotation_matrix().transform_vector(v),
+            m.transform_vector(v)
+        );
+        assert_eq!(q.conjugate().rotate(q.rotate(v)), v);
+
+        // Halfway between no rotation and 170 degrees, and across the shorter arc to -170.
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let half = Quaternion::identity().slerp(Quaternion::from_axis_angle(y, 170.0), 0.5);
+        assert_eq!(half.rotate(v), Mat4::rotation(y, 85.0).transform_vector(v));
+        let from = Quaternion::from_axis_angle(y, 170.0);
+        let across = from.slerp(Quaternion::from_axis_angle(y, -170.0), 0.5);
+        assert_eq!(
+            across.rotate(v),
+            Mat4::rotation(y, 180.0).transform_vector(v)
+        );
+    }
+}
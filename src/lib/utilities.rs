@@ -33,5 +33,161 @@ pub fn random_float_range(min: f64, max: f64) -> f64 {
 /// Function that returns usize random values in the [min, max] range.
 pub fn random_usize_range(min: usize, max: usize) -> usize {
     let mut rng = thread_rng();
-    rng.gen_range(min..=(max + 1))
+    rng.gen_range(min..=max)
+}
+
+/// Function that returns the real roots of a x^2 + b x + c in ascending order. Degenerates to
+/// the linear equation when 'a' is zero.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < NEAR_ZERO {
+        return if b.abs() < NEAR_ZERO {
+            vec![]
+        } else {
+            vec![-c / b]
+        };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // Avoids the cancellation of b and the square root of the discriminant.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return vec![0.0, 0.0];
+    }
+    let (r0, r1) = (q / a, c / q);
+    if r0 < r1 {
+        vec![r0, r1]
+    } else {
+        vec![r1, r0]
+    }
+}
+
+/// Function that returns the real roots of a x^3 + b x^2 + c x + d in ascending order.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < NEAR_ZERO {
+        return solve_quadratic(b, c, d);
+    }
+    // Normal form x^3 + A x^2 + B x + C, reduced to y^3 + p y + q with x = y - A / 3.
+    let (a2, a1, a0) = (b / a, c / a, d / a);
+    let p = a1 - a2 * a2 / 3.0;
+    let q = 2.0 * a2 * a2 * a2 / 27.0 - a2 * a1 / 3.0 + a0;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let mut roots = if discriminant.abs() < NEAR_ZERO * NEAR_ZERO {
+        if q.abs() < NEAR_ZERO {
+            vec![0.0]
+        } else {
+            let u = (-q / 2.0).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots.
+        let phi = (-q / 2.0 / (-p * p * p / 27.0).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos()
+            / 3.0;
+        let m = 2.0 * (-p / 3.0).sqrt();
+        vec![
+            m * phi.cos(),
+            m * (phi + 2.0 * PI / 3.0).cos(),
+            m * (phi - 2.0 * PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt_discriminant).cbrt() + (-q / 2.0 - sqrt_discriminant).cbrt()]
+    };
+
+    for root in roots.iter_mut() {
+        *root -= a2 / 3.0;
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// Function that returns the real roots of a x^4 + b x^3 + c x^2 + d x + e in ascending order,
+/// using Ferrari's method followed by a few Newton iterations to refine each root.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < NEAR_ZERO {
+        return solve_cubic(b, c, d, e);
+    }
+    // Normal form x^4 + A x^3 + B x^2 + C x + D, reduced to y^4 + p y^2 + q y + r with
+    // x = y - A / 4.
+    let (a3, a2, a1, a0) = (b / a, c / a, d / a, e / a);
+    let sq = a3 * a3;
+    let p = -3.0 / 8.0 * sq + a2;
+    let q = sq * a3 / 8.0 - a3 * a2 / 2.0 + a1;
+    let r = -3.0 / 256.0 * sq * sq + sq * a2 / 16.0 - a3 * a1 / 4.0 + a0;
+
+    let mut roots = if r.abs() < NEAR_ZERO {
+        // y (y^3 + p y + q) = 0
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // One real root of the resolvent cubic splits the quartic into two quadratics.
+        let z = solve_cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0)[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let root_or_zero = |value: f64| {
+            if value.abs() < NEAR_ZERO {
+                Some(0.0)
+            } else if value > 0.0 {
+                Some(value.sqrt())
+            } else {
+                None
+            }
+        };
+        match (root_or_zero(u), root_or_zero(v)) {
+            (Some(u), Some(v)) => {
+                let v = if q < 0.0 { -v } else { v };
+                let mut roots = solve_quadratic(1.0, v, z - u);
+                roots.extend(solve_quadratic(1.0, -v, z + u));
+                roots
+            }
+            _ => vec![],
+        }
+    };
+
+    let polynomial = |x: f64| (((a * x + b) * x + c) * x + d) * x + e;
+    let derivative = |x: f64| ((4.0 * a * x + 3.0 * b) * x + 2.0 * c) * x + d;
+    for root in roots.iter_mut() {
+        *root -= a3 / 4.0;
+        for _ in 0..3 {
+            let slope = derivative(*root);
+            if slope.abs() < NEAR_ZERO {
+                break;
+            }
+            *root -= polynomial(*root) / slope;
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_cubic, solve_quadratic, solve_quartic};
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "roots {:?}", roots);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-6, "roots {:?}", roots);
+        }
+    }
+
+    #[test]
+    fn polynomial_roots() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        assert_roots(solve_cubic(1.0, 0.0, 0.0, -8.0), &[2.0]);
+        // (x - 1)(x - 2)(x - 3)(x - 4) and (x^2 - 1)(x^2 + 1).
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, -1.0), &[-1.0, 1.0]);
+        assert_roots(solve_quartic(2.0, 0.0, 2.0, 0.0, 2.0), &[]);
+    }
 }
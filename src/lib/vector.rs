@@ -7,7 +7,7 @@ use crate::color::Color;
 use crate::utilities::{random_float, random_float_range, EPSILON, NEAR_ZERO};
 use derive_more::{Add, Neg, Sub};
 use rand::Rng;
-use std::ops::{Add, Div, Index, Mul};
+use std::ops::{Div, Index, IndexMut, Mul};
 
 /// Type representing a geometric 3D vector with X, Y and Z coordinates.
 #[derive(Debug, Copy, Clone, Add, Sub, Neg)]
@@ -177,6 +177,17 @@ impl Index<usize> for Vec3 {
     }
 }
 
+impl IndexMut<usize> for Vec3 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("wrong index for Vec3: {}", index),
+        }
+    }
+}
+
 impl Default for Vec3 {
     fn default() -> Self {
         Vec3::zeroes()
@@ -197,7 +208,7 @@ impl PartialEq for Vec3 {
     fn eq(&self, other: &Self) -> bool {
         if (self.x - other.x).abs() > EPSILON
             || (self.y - other.y).abs() > EPSILON
-            || (self.y - other.y).abs() > EPSILON
+            || (self.z - other.z).abs() > EPSILON
         {
             return false;
         }
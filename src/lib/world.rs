@@ -0,0 +1,218 @@
+//! This module defines the World type, which accelerates the shapes of a Scene with a Bounding
+//! Volume Hierarchy, or another acceleration structure, while testing unbounded shapes, such as
+//! planes, separately.
+
+#![warn(missing_docs, missing_debug_implementations)]
+
+use crate::aabb::AaBb;
+use crate::accelerator::{Accelerator, AcceleratorKind, Accelerators, TraversalStats};
+use crate::hittable::{HitRecord, Hittable, HittableList};
+use crate::ray::Ray;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Ratio of the cost of a refitted hierarchy to its cost when built above which it is rebuilt.
+pub const REBUILD_THRESHOLD: f64 = 1.5;
+
+/// Traversal statistics shared by the threads that trace Rays through a World.
+#[derive(Debug, Default)]
+struct SharedStats {
+    /// Number of Rays traced.
+    rays: AtomicU64,
+    /// Number of nodes or cells visited.
+    node_visits: AtomicU64,
+    /// Number of Ray-shape intersection tests.
+    primitive_tests: AtomicU64,
+}
+
+/// The shapes of a Scene, split into an acceleration structure over the bounded shapes and a
+/// list of unbounded ones.
+#[derive(Debug, Clone)]
+pub struct World {
+    /// Acceleration structure over the shapes that have a bounding box.
+    accelerator: Accelerators,
+    /// Shapes without a bounding box, tested against every ray.
+    unbounded: HittableList,
+    /// Statistics of the Rays traced so far, when they are collected.
+    stats: Option<Arc<SharedStats>>,
+}
+
+impl World {
+    /// Creates and returns an owned World from the shapes of a Scene, with a Bounding Volume
+    /// Hierarchy over the bounding boxes for the time interval of the Camera shutter.
+    pub fn new(list: HittableList, time0: f64, time1: f64) -> Self {
+        Self::with_accelerator(list, AcceleratorKind::Bvh, time0, time1)
+    }
+
+    /// Creates and returns an owned World from the shapes of a Scene, with an acceleration
+    /// structure of the given kind over the bounding boxes for the time interval of the Camera
+    /// shutter.
+    pub fn with_accelerator(
+        list: HittableList,
+        kind: AcceleratorKind,
+        time0: f64,
+        time1: f64,
+    ) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = list
+            .objects
+            .into_iter()
+            .partition(|shape| shape.bounding_box(time0, time1).is_some());
+
+        let bounded = HittableList { objects: bounded };
+        Self {
+            accelerator: Accelerators::new(kind, &bounded, time0, time1),
+            unbounded: HittableList { objects: unbounded },
+            stats: None,
+        }
+    }
+
+    /// Updates the acceleration structure to the time interval of the shutter of a new frame,
+    /// so that moving shapes, such as Animated instances, are bounded where they are during that
+    /// frame. A hierarchy is refitted, or rebuilt when refitting degraded it past the
+    /// REBUILD_THRESHOLD; other structures are rebuilt. Returns whether it was rebuilt.
+    pub fn update(&mut self, time0: f64, time1: f64) -> bool {
+        self.accelerator.update(time0, time1, REBUILD_THRESHOLD)
+    }
+
+    /// Returns the kind of the acceleration structure.
+    pub fn accelerator_kind(&self) -> AcceleratorKind {
+        self.accelerator.kind()
+    }
+
+    /// Starts counting the nodes visited and the shapes tested by the Rays traced from now on.
+    pub fn collect_stats(&mut self) {
+        self.stats = Some(Arc::default());
+    }
+
+    /// Returns the statistics of the Rays traced since 'collect_stats' was called, or None when
+    /// they are not collected.
+    pub fn stats(&self) -> Option<TraversalStats> {
+        self.stats.as_ref().map(|stats| TraversalStats {
+            rays: stats.rays.load(Ordering::Relaxed),
+            node_visits: stats.node_visits.load(Ordering::Relaxed),
+            primitive_tests: stats.primitive_tests.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Returns the number of shapes that are tested outside the hierarchy.
+    pub fn total_unbounded(&self) -> usize {
+        self.unbounded.total_shapes()
+    }
+}
+
+impl Hittable for World {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit_bounded = match &self.stats {
+            Some(shared) => {
+                // Count locally and publish once per Ray, to keep the threads apart.
+                let mut stats = TraversalStats {
+                    rays: 1,
+                    primitive_tests: self.unbounded.total_shapes() as u64,
+                    ..TraversalStats::default()
+                };
+                let hit = self.accelerator.hit_counted(ray, t_min, t_max, &mut stats);
+                shared.rays.fetch_add(stats.rays, Ordering::Relaxed);
+                shared
+                    .node_visits
+                    .fetch_add(stats.node_visits, Ordering::Relaxed);
+                shared
+                    .primitive_tests
+                    .fetch_add(stats.primitive_tests, Ordering::Relaxed);
+                hit
+            }
+            None => self.accelerator.hit(ray, t_min, t_max),
+        };
+        let closest_so_far = hit_bounded.as_ref().map_or(t_max, |hit| hit.t);
+
+        self.unbounded
+            .hit(ray, t_min, closest_so_far)
+            .or(hit_bounded)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<AaBb> {
+        if self.total_unbounded() > 0 {
+            return None;
+        }
+        self.accelerator.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::World;
+    use crate::accelerator::AcceleratorKind;
+    use crate::hittable::{HitRecord, Hittable, HittableList};
+    use crate::ray::Ray;
+    use crate::shapes::plane::Plane;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::HittableObjects;
+    use crate::vector::{Point3, Vec3};
+
+    #[test]
+    fn world_tests_planes_outside_the_hierarchy() {
+        let material = HitRecord::default().material;
+        let mut list = HittableList::default();
+        list.add(HittableObjects::Plane(Plane::new(
+            Point3::zeroes(),
+            Vec3::new(0.0, 1.0, 0.0),
+            material.clone(),
+        )));
+        for x in 0..3 {
+            list.add(HittableObjects::Sphere(Sphere::new(
+                Point3::new(x as f64 * 3.0, 1.0, 0.0),
+                1.0,
+                material.clone(),
+            )));
+        }
+        let world = World::new(list, 0.0, 1.0);
+        assert_eq!(world.total_unbounded(), 1);
+        assert!(world.bounding_box(0.0, 1.0).is_none());
+
+        // Straight down onto the middle sphere, then next to it onto the plane.
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let ray = Ray::new(Point3::new(3.0, 5.0, 0.0), down, 0.0);
+        assert!((world.hit(&ray, 0.001, f64::INFINITY).unwrap().t - 3.0).abs() < 1e-9);
+        let ray = Ray::new(Point3::new(1.5, 5.0, 0.0), down, 0.0);
+        assert!((world.hit(&ray, 0.001, f64::INFINITY).unwrap().t - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn world_counts_traversal_stats_for_every_accelerator() {
+        let material = HitRecord::default().material;
+        let mut list = HittableList::default();
+        for x in 0..10 {
+            for z in 0..10 {
+                list.add(HittableObjects::Sphere(Sphere::new(
+                    Point3::new(x as f64 * 3.0, 1.0, z as f64 * 3.0),
+                    1.0,
+                    material.clone(),
+                )));
+            }
+        }
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        for kind in [
+            AcceleratorKind::Bvh,
+            AcceleratorKind::Grid,
+            AcceleratorKind::KdTree,
+        ] {
+            let mut world = World::with_accelerator(list.clone(), kind, 0.0, 1.0);
+            assert_eq!(world.accelerator_kind(), kind);
+            assert!(world.stats().is_none());
+            world.collect_stats();
+
+            // Straight down onto every sphere: each ray tests a few of the hundred spheres.
+            for x in 0..10 {
+                for z in 0..10 {
+                    let origin = Point3::new(x as f64 * 3.0, 5.0, z as f64 * 3.0);
+                    let hit = world.hit(&Ray::new(origin, down, 0.0), 0.001, f64::INFINITY);
+                    assert!((hit.unwrap().t - 3.0).abs() < 1e-9);
+                }
+            }
+            let stats = world.stats().unwrap();
+            assert_eq!(stats.rays, 100);
+            assert!(stats.node_visits_per_ray() >= 1.0);
+            assert!(stats.primitive_tests_per_ray() >= 1.0);
+            assert!(stats.primitive_tests_per_ray() < 10.0, "{:?}", kind);
+        }
+    }
+}